@@ -0,0 +1,60 @@
+use std::fmt;
+
+/// # ❌ Errors returned by the Merkle Tree functions
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum MerkleError {
+    /// The requested leaf is not in the tree
+    LeafNotFound,
+    /// The operation needs at least one leaf
+    EmptyTree,
+    /// The index does not point to a leaf of the tree
+    IndexOutOfRange { index: usize, len: usize },
+    /// A side must be either `0` (LEFT) or `1` (RIGHT)
+    InvalidSide(u8),
+    /// The proof cannot have been produced by any tree
+    MalformedProof,
+    /// These leaves cannot be proven together in the multiproof layout
+    UnsupportedMultiProof,
+    /// The proof is well formed but leads to another root
+    RootMismatch,
+    /// The consistency proof does not lead to the root of the old tree
+    OldRootMismatch,
+    /// The consistency proof does not lead to the root of the new tree
+    NewRootMismatch,
+    /// The operation is only defined for trees that promote unpaired nodes
+    UnsupportedOddNodeStrategy,
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MerkleError::LeafNotFound => write!(f, "Leaf does not exist in the tree"),
+            MerkleError::EmptyTree => write!(f, "The tree has no leaves"),
+            MerkleError::IndexOutOfRange { index, len } => {
+                write!(f, "Index {index} is out of range for {len} leaves")
+            }
+            MerkleError::InvalidSide(num) => write!(
+                f,
+                "Invalid value `{num}` for Side enum, must be either `0` or `1`"
+            ),
+            MerkleError::MalformedProof => write!(f, "The proof is malformed"),
+            MerkleError::UnsupportedMultiProof => write!(
+                f,
+                "These leaves cannot be proven together without a commutative hasher"
+            ),
+            MerkleError::RootMismatch => write!(f, "The proof does not lead to the expected root"),
+            MerkleError::OldRootMismatch => {
+                write!(f, "The consistency proof does not lead to the old root")
+            }
+            MerkleError::NewRootMismatch => {
+                write!(f, "The consistency proof does not lead to the new root")
+            }
+            MerkleError::UnsupportedOddNodeStrategy => write!(
+                f,
+                "This operation needs a tree built with `OddNodeStrategy::Promote`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MerkleError {}
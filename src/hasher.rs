@@ -1,21 +1,40 @@
-use crate::{Hash, Leaf};
+use crate::{Hash, Leaf, HASH_LEN};
 use tiny_keccak::{Hasher as KeccakHasher, Keccak};
 
 /// Trait for custom hash functions
 ///
-/// Implement this trait to use a custom hash function with the Merkle Tree
-pub trait Hashable: Clone {
+/// Implement this trait to use a custom hash function with the Merkle Tree.
+/// `N` is the size of its output, 32 bytes unless stated otherwise.
+pub trait Hashable<const N: usize = HASH_LEN>: Clone {
     /// Hash a single piece of data
-    fn hash(&self, data: &[u8], buffer: &mut Hash);
+    fn hash(&self, data: &[u8], buffer: &mut Hash<N>);
+
+    /// Hash raw data into a leaf (defaults to `hash`)
+    fn hash_leaf(&self, data: &[u8], buffer: &mut Hash<N>) {
+        self.hash(data, buffer);
+    }
 
     /// Hash two leaves together (used for building the tree)
-    fn hash_nodes(&self, left: &Leaf, right: &Leaf, buffer: &mut Hash) {
-        let mut concat = [0u8; 64];
-        concat[..32].copy_from_slice(left);
-        concat[32..].copy_from_slice(right);
+    fn hash_nodes(&self, left: &Leaf<N>, right: &Leaf<N>, buffer: &mut Hash<N>) {
+        with_concat(&[left, right], |concat| self.hash(concat, buffer));
+    }
+}
+
+/// Call `f` with the concatenation of `parts`, on the stack when it is small
+fn with_concat<R>(parts: &[&[u8]], f: impl FnOnce(&[u8]) -> R) -> R {
+    let len = parts.iter().map(|part| part.len()).sum();
 
-        self.hash(&concat, buffer);
+    let mut stack = [0u8; 4 * HASH_LEN + 1];
+    if len > stack.len() {
+        return f(&parts.concat());
     }
+
+    let mut start = 0;
+    for part in parts {
+        stack[start..start + part.len()].copy_from_slice(part);
+        start += part.len();
+    }
+    f(&stack[..len])
 }
 
 /// Default hasher using Keccak256
@@ -35,3 +54,441 @@ impl Default for Keccak256Hasher {
         Keccak256Hasher
     }
 }
+
+/// SHA-256 hasher, enabled by the `sha2` feature
+#[cfg(feature = "sha2")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha256Hasher;
+
+#[cfg(feature = "sha2")]
+impl Hashable for Sha256Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        use sha2::{Digest, Sha256};
+
+        buffer.copy_from_slice(&Sha256::digest(data));
+    }
+}
+
+/// SHA-512 hasher with 64-byte hashes, enabled by the `sha2` feature
+#[cfg(feature = "sha2")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha512Hasher;
+
+#[cfg(feature = "sha2")]
+impl Hashable<64> for Sha512Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash<64>) {
+        use sha2::{Digest, Sha512};
+
+        buffer.copy_from_slice(&Sha512::digest(data));
+    }
+}
+
+/// SHA3-256 hasher, enabled by the `sha3` feature
+///
+/// This is the FIPS 202 function, its padding differs from `Keccak256Hasher`.
+#[cfg(feature = "sha3")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sha3_256Hasher;
+
+#[cfg(feature = "sha3")]
+impl Hashable for Sha3_256Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        use sha3::{Digest, Sha3_256};
+
+        buffer.copy_from_slice(&Sha3_256::digest(data));
+    }
+}
+
+/// BLAKE2b hasher with a 32-byte output, enabled by the `blake2` feature
+#[cfg(feature = "blake2")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake2b256Hasher;
+
+#[cfg(feature = "blake2")]
+impl Hashable for Blake2b256Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        use blake2::digest::consts::U32;
+        use blake2::{Blake2b, Digest};
+
+        buffer.copy_from_slice(&Blake2b::<U32>::digest(data));
+    }
+}
+
+/// BLAKE2b hasher with 64-byte hashes, enabled by the `blake2` feature
+#[cfg(feature = "blake2")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake2b512Hasher;
+
+#[cfg(feature = "blake2")]
+impl Hashable<64> for Blake2b512Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash<64>) {
+        use blake2::{Blake2b512, Digest};
+
+        buffer.copy_from_slice(&Blake2b512::digest(data));
+    }
+}
+
+/// BLAKE3 hasher, enabled by the `blake3` feature
+#[cfg(feature = "blake3")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake3Hasher;
+
+#[cfg(feature = "blake3")]
+impl Hashable for Blake3Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        *buffer = *blake3::hash(data).as_bytes();
+    }
+}
+
+/// Keyed BLAKE3 hasher, enabled by the `blake3` feature
+///
+/// Every hash is a BLAKE3 MAC under the 32-byte key, so roots and proofs can
+/// only be reproduced by holders of the key. `Debug` does not show it.
+#[cfg(feature = "blake3")]
+#[derive(Clone)]
+pub struct KeyedBlake3Hasher {
+    key: [u8; 32],
+}
+
+#[cfg(feature = "blake3")]
+impl KeyedBlake3Hasher {
+    pub fn new(key: [u8; 32]) -> Self {
+        KeyedBlake3Hasher { key }
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl std::fmt::Debug for KeyedBlake3Hasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeyedBlake3Hasher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl Hashable for KeyedBlake3Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        *buffer = *blake3::keyed_hash(&self.key, data).as_bytes();
+    }
+}
+
+/// HMAC-SHA256 hasher, enabled by the `hmac` feature
+///
+/// Every hash is an HMAC under the key, so roots and proofs can only be
+/// reproduced by holders of the key. `Debug` does not show it.
+#[cfg(feature = "hmac")]
+#[derive(Clone)]
+pub struct HmacSha256Hasher {
+    /// Keyed once, cloned for every hash
+    mac: hmac::Hmac<sha2::Sha256>,
+}
+
+#[cfg(feature = "hmac")]
+impl HmacSha256Hasher {
+    /// Keys of any length are accepted, as HMAC allows
+    pub fn new(key: &[u8]) -> Self {
+        use hmac::Mac;
+
+        HmacSha256Hasher {
+            mac: hmac::Hmac::new_from_slice(key).expect("HMAC accepts keys of any length"),
+        }
+    }
+}
+
+#[cfg(feature = "hmac")]
+impl std::fmt::Debug for HmacSha256Hasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HmacSha256Hasher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "hmac")]
+impl Hashable for HmacSha256Hasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        use hmac::Mac;
+
+        let mut mac = self.mac.clone();
+        mac.update(data);
+        buffer.copy_from_slice(&mac.finalize().into_bytes());
+    }
+}
+
+/// Hasher for any RustCrypto `Digest` with a 32-byte output, enabled by the `digest` feature
+#[cfg(feature = "digest")]
+pub struct DigestHasher<D>(std::marker::PhantomData<fn() -> D>);
+
+#[cfg(feature = "digest")]
+impl<D> DigestHasher<D> {
+    pub fn new() -> Self {
+        DigestHasher(std::marker::PhantomData)
+    }
+}
+
+// Written by hand so `D` itself needs neither `Clone` nor `Debug`
+#[cfg(feature = "digest")]
+impl<D> Clone for DigestHasher<D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<D> Copy for DigestHasher<D> {}
+
+#[cfg(feature = "digest")]
+impl<D> Default for DigestHasher<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<D> std::fmt::Debug for DigestHasher<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DigestHasher<{}>", std::any::type_name::<D>())
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<D> Hashable for DigestHasher<D>
+where
+    D: digest::Digest + digest::OutputSizeUser<OutputSize = digest::consts::U32>,
+{
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        buffer.copy_from_slice(&D::digest(data));
+    }
+}
+
+/// Poseidon hasher over the BN254 scalar field, enabled by the `poseidon` feature
+///
+/// Parameters match circomlib's `Poseidon(n)`, so roots and proofs agree with
+/// circom and Noir verifiers. `hash_nodes` is `Poseidon(2)` of both children
+/// read as field elements, `hash` is `Poseidon(1)` of the data read as a
+/// big-endian number. Outputs are big-endian.
+///
+/// Inputs are reduced modulo the field order, like a circuit would, so a leaf
+/// at or above the order hashes as its remainder: check untrusted leaves with
+/// `is_canonical` first.
+#[cfg(feature = "poseidon")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PoseidonHasher;
+
+#[cfg(feature = "poseidon")]
+impl PoseidonHasher {
+    /// Whether `element` is a big-endian number below the BN254 field order
+    pub fn is_canonical(element: &Hash) -> bool {
+        use ark_bn254::Fr;
+        use ark_ff::{BigInteger, PrimeField};
+
+        Fr::from_be_bytes_mod_order(element)
+            .into_bigint()
+            .to_bytes_be()
+            == element
+    }
+
+    fn poseidon(inputs: &[&[u8]], buffer: &mut Hash) {
+        use ark_bn254::Fr;
+        use ark_ff::{BigInteger, PrimeField};
+        use light_poseidon::{Poseidon, PoseidonHasher as _};
+        use std::cell::RefCell;
+
+        // building the round constants costs more than a hash, so each thread
+        // keeps one sponge per arity, which clears its state after every hash
+        thread_local! {
+            static SPONGES: [RefCell<Poseidon<Fr>>; 2] = [1, 2].map(|arity| {
+                RefCell::new(Poseidon::<Fr>::new_circom(arity).expect("circom parameters"))
+            });
+        }
+
+        let inputs: Vec<Fr> = inputs
+            .iter()
+            .map(|input| Fr::from_be_bytes_mod_order(input))
+            .collect();
+        let hash = SPONGES.with(|sponges| {
+            sponges[inputs.len() - 1]
+                .borrow_mut()
+                .hash(&inputs)
+                .expect("as many inputs as parameters")
+        });
+
+        buffer.copy_from_slice(&hash.into_bigint().to_bytes_be());
+    }
+}
+
+#[cfg(feature = "poseidon")]
+impl Hashable for PoseidonHasher {
+    fn hash(&self, data: &[u8], buffer: &mut Hash) {
+        Self::poseidon(&[data], buffer);
+    }
+
+    fn hash_nodes(&self, left: &Leaf, right: &Leaf, buffer: &mut Hash) {
+        Self::poseidon(&[left, right], buffer);
+    }
+}
+
+/// Hasher wrapper keeping the first `M` bytes of the `N`-byte hashes of `H`
+///
+/// Gives compact proofs, e.g. `Hashable<20>` from Keccak-256, and `M` must not
+/// exceed `N`. Nodes zero-extend both children to `N` bytes, as `abi.encode`
+/// does with `bytes20`, and truncate what the `hash_nodes` of `H` gives them,
+/// so wrappers like `SortedPairHasher` keep hashing the nodes their way.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TruncatedHasher<H = Keccak256Hasher, const N: usize = HASH_LEN>(pub H);
+
+impl<const M: usize, const N: usize, H: Hashable<N>> Hashable<M> for TruncatedHasher<H, N> {
+    fn hash(&self, data: &[u8], buffer: &mut Hash<M>) {
+        const { assert!(M <= N, "cannot truncate a hash to more bytes than it has") };
+
+        let mut full = [0u8; N];
+        self.0.hash(data, &mut full);
+        buffer.copy_from_slice(&full[..M]);
+    }
+
+    fn hash_leaf(&self, data: &[u8], buffer: &mut Hash<M>) {
+        const { assert!(M <= N, "cannot truncate a hash to more bytes than it has") };
+
+        let mut full = [0u8; N];
+        self.0.hash_leaf(data, &mut full);
+        buffer.copy_from_slice(&full[..M]);
+    }
+
+    fn hash_nodes(&self, left: &Leaf<M>, right: &Leaf<M>, buffer: &mut Hash<M>) {
+        const { assert!(M <= N, "cannot truncate a hash to more bytes than it has") };
+
+        let (mut full_left, mut full_right) = ([0u8; N], [0u8; N]);
+        full_left[..M].copy_from_slice(left);
+        full_right[..M].copy_from_slice(right);
+
+        let mut full = [0u8; N];
+        self.0.hash_nodes(&full_left, &full_right, &mut full);
+        buffer.copy_from_slice(&full[..M]);
+    }
+}
+
+/// Hasher wrapper that hashes every pair as `min || max`
+///
+/// This is how OpenZeppelin's `MerkleProof.verify` hashes its nodes: pairs are
+/// commutative, so proofs need no side and can be exported as a plain
+/// `Vec<Hash>` (see `proof_hashes` and `merkle_proof_check_sorted`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SortedPairHasher<H = Keccak256Hasher>(pub H);
+
+impl<const N: usize, H: Hashable<N>> Hashable<N> for SortedPairHasher<H> {
+    fn hash(&self, data: &[u8], buffer: &mut Hash<N>) {
+        self.0.hash(data, buffer);
+    }
+
+    fn hash_leaf(&self, data: &[u8], buffer: &mut Hash<N>) {
+        self.0.hash_leaf(data, buffer);
+    }
+
+    fn hash_nodes(&self, left: &Leaf<N>, right: &Leaf<N>, buffer: &mut Hash<N>) {
+        if left <= right {
+            self.0.hash_nodes(left, right, buffer);
+        } else {
+            self.0.hash_nodes(right, left, buffer);
+        }
+    }
+}
+
+/// Hasher wrapper that prefixes the data of every leaf with a secret salt
+///
+/// Low-entropy leaves, e.g. email addresses, can no longer be brute-forced
+/// from their hash without the salt. Nodes are hashed by `H` as usual, as they
+/// only commit to salted leaves. `Debug` does not show the salt.
+#[derive(Clone)]
+pub struct SaltedHasher<H = Keccak256Hasher> {
+    pub inner: H,
+    salt: Vec<u8>,
+}
+
+impl<H> SaltedHasher<H> {
+    pub fn new(inner: H, salt: &[u8]) -> Self {
+        SaltedHasher {
+            inner,
+            salt: salt.to_vec(),
+        }
+    }
+}
+
+impl<H: std::fmt::Debug> std::fmt::Debug for SaltedHasher<H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaltedHasher")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const N: usize, H: Hashable<N>> Hashable<N> for SaltedHasher<H> {
+    fn hash(&self, data: &[u8], buffer: &mut Hash<N>) {
+        with_concat(&[&self.salt, data], |salted| {
+            self.inner.hash(salted, buffer)
+        });
+    }
+
+    fn hash_leaf(&self, data: &[u8], buffer: &mut Hash<N>) {
+        with_concat(&[&self.salt, data], |salted| {
+            self.inner.hash_leaf(salted, buffer)
+        });
+    }
+
+    fn hash_nodes(&self, left: &Leaf<N>, right: &Leaf<N>, buffer: &mut Hash<N>) {
+        self.inner.hash_nodes(left, right, buffer);
+    }
+}
+
+/// Hasher wrapper that prefixes leaves and nodes with a different byte
+///
+/// As in RFC 6962, leaves are hashed as `H(0x00 || data)` and nodes as
+/// `H(0x01 || left || right)`, so an internal node can never be passed off as
+/// a leaf (second-preimage attack). Leaves must be hashed with `hash_leaf`,
+/// see `hash_leaves` and `MerkleTree::new_from_data`.
+#[derive(Clone, Copy, Debug)]
+pub struct DomainSeparatedHasher<H = Keccak256Hasher> {
+    pub inner: H,
+    pub leaf_prefix: u8,
+    pub node_prefix: u8,
+}
+
+impl<H> DomainSeparatedHasher<H> {
+    pub const LEAF_PREFIX: u8 = 0x00;
+    pub const NODE_PREFIX: u8 = 0x01;
+
+    /// Use the RFC 6962 prefixes, `0x00` for leaves and `0x01` for nodes
+    pub fn new(inner: H) -> Self {
+        Self::with_prefixes(inner, Self::LEAF_PREFIX, Self::NODE_PREFIX)
+    }
+
+    pub fn with_prefixes(inner: H, leaf_prefix: u8, node_prefix: u8) -> Self {
+        DomainSeparatedHasher {
+            inner,
+            leaf_prefix,
+            node_prefix,
+        }
+    }
+}
+
+impl<H: Default> Default for DomainSeparatedHasher<H> {
+    fn default() -> Self {
+        Self::new(H::default())
+    }
+}
+
+impl<const N: usize, H: Hashable<N>> Hashable<N> for DomainSeparatedHasher<H> {
+    fn hash(&self, data: &[u8], buffer: &mut Hash<N>) {
+        self.inner.hash(data, buffer);
+    }
+
+    fn hash_leaf(&self, data: &[u8], buffer: &mut Hash<N>) {
+        let mut prefixed = Vec::with_capacity(data.len() + 1);
+        prefixed.push(self.leaf_prefix);
+        prefixed.extend_from_slice(data);
+
+        self.inner.hash(&prefixed, buffer);
+    }
+
+    fn hash_nodes(&self, left: &Leaf<N>, right: &Leaf<N>, buffer: &mut Hash<N>) {
+        with_concat(&[&[self.node_prefix], left, right], |concat| {
+            self.inner.hash(concat, buffer)
+        });
+    }
+}
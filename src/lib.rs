@@ -1,18 +1,54 @@
 use node::Node;
 
+pub mod error;
 pub mod hasher;
+pub mod merkle_consistency_check;
+pub mod merkle_consistency_proof;
+pub mod merkle_layers;
+pub mod merkle_multiproof;
+pub mod merkle_multiproof_check;
 pub mod merkle_proof;
 pub mod merkle_proof_check;
 pub mod merkle_proof_mixed;
 pub mod merkle_root;
+pub mod mmr;
 pub mod node;
+pub mod options;
+#[cfg(feature = "sha2")]
+pub mod rfc6962;
+pub mod sparse;
+#[cfg(feature = "tlog")]
+pub mod tlog;
 pub mod tree;
 pub mod utils;
 
-pub type Proof = Vec<Node>;
-pub type Hash = [u8; 32];
-pub type Leaf = [u8; 32];
-pub type Root = [u8; 32];
+/// Size of every hash, unless the hasher picks another one with `Hashable<N>`
+pub const HASH_LEN: usize = 32;
 
-// Re-export the hasher types for convenience
-pub use hasher::{Hashable, Keccak256Hasher};
+pub type Proof<const N: usize = HASH_LEN> = Vec<Node<N>>;
+pub type Hash<const N: usize = HASH_LEN> = [u8; N];
+pub type Leaf<const N: usize = HASH_LEN> = [u8; N];
+pub type Root<const N: usize = HASH_LEN> = [u8; N];
+
+// Re-export the error, hasher and option types for convenience
+pub use error::MerkleError;
+pub use hasher::{
+    DomainSeparatedHasher, Hashable, Keccak256Hasher, SaltedHasher, SortedPairHasher,
+    TruncatedHasher,
+};
+pub use options::{EmptyRoot, OddNodeStrategy, RemoveMode, TreeOptions};
+
+#[cfg(feature = "digest")]
+pub use hasher::DigestHasher;
+#[cfg(feature = "hmac")]
+pub use hasher::HmacSha256Hasher;
+#[cfg(feature = "poseidon")]
+pub use hasher::PoseidonHasher;
+#[cfg(feature = "sha3")]
+pub use hasher::Sha3_256Hasher;
+#[cfg(feature = "blake2")]
+pub use hasher::{Blake2b256Hasher, Blake2b512Hasher};
+#[cfg(feature = "blake3")]
+pub use hasher::{Blake3Hasher, KeyedBlake3Hasher};
+#[cfg(feature = "sha2")]
+pub use hasher::{Sha256Hasher, Sha512Hasher};
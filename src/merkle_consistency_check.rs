@@ -0,0 +1,101 @@
+use crate::error::MerkleError;
+use crate::hasher::Hashable;
+use crate::merkle_proof_check::MAX_PROOF_LEN;
+use crate::{Hash, Root};
+
+/// Check that the tree of `old_size` leaves with `old_root` is a prefix of the
+/// tree of `new_size` leaves with `new_root`
+///
+/// Follows RFC 9162 §2.1.4.2. Returns `MerkleError::OldRootMismatch` or
+/// `MerkleError::NewRootMismatch` when the proof is well formed but does not
+/// lead to the given root.
+pub fn merkle_consistency_check<const N: usize, H: Hashable<N>>(
+    proof: &[Hash<N>],
+    old_size: usize,
+    new_size: usize,
+    old_root: Root<N>,
+    new_root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if old_size > new_size {
+        return Err(MerkleError::IndexOutOfRange {
+            index: old_size,
+            len: new_size,
+        });
+    }
+    if proof.len() > 2 * MAX_PROOF_LEN {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    // an empty tree is a prefix of any tree
+    if old_size == 0 {
+        if !proof.is_empty() {
+            return Err(MerkleError::MalformedProof);
+        }
+        return Ok(());
+    }
+    if old_size == new_size {
+        if !proof.is_empty() {
+            return Err(MerkleError::MalformedProof);
+        }
+        if old_root != new_root {
+            return Err(MerkleError::NewRootMismatch);
+        }
+        return Ok(());
+    }
+
+    // the old tree is a complete subtree of the new one, its root is not in the proof
+    let mut proof = proof.to_vec();
+    if old_size.is_power_of_two() {
+        proof.insert(0, old_root);
+    }
+    let (first, rest) = proof.split_first().ok_or(MerkleError::MalformedProof)?;
+
+    let mut node_index = old_size - 1;
+    let mut last_index = new_size - 1;
+    while node_index % 2 == 1 {
+        node_index >>= 1;
+        last_index >>= 1;
+    }
+
+    let mut old_hash = *first;
+    let mut new_hash = *first;
+
+    for sibling in rest {
+        if last_index == 0 {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let mut buffer = [0u8; N];
+        if node_index % 2 == 1 || node_index == last_index {
+            hasher.hash_nodes(sibling, &old_hash, &mut buffer);
+            old_hash = buffer;
+            hasher.hash_nodes(sibling, &new_hash, &mut buffer);
+            new_hash = buffer;
+
+            // skip the layers where this node is promoted
+            while node_index.is_multiple_of(2) && node_index != 0 {
+                node_index >>= 1;
+                last_index >>= 1;
+            }
+        } else {
+            hasher.hash_nodes(&new_hash, sibling, &mut buffer);
+            new_hash = buffer;
+        }
+
+        node_index >>= 1;
+        last_index >>= 1;
+    }
+
+    if last_index != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+    if old_hash != old_root {
+        return Err(MerkleError::OldRootMismatch);
+    }
+    if new_hash != new_root {
+        return Err(MerkleError::NewRootMismatch);
+    }
+
+    Ok(())
+}
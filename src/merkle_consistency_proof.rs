@@ -0,0 +1,91 @@
+use crate::error::MerkleError;
+use crate::hasher::Hashable;
+use crate::merkle_layers::merkle_layers;
+use crate::{Hash, Leaf};
+
+/// Prove that the tree of the first `old_size` leaves is a prefix of the tree of all `leaves`
+///
+/// Follows RFC 6962 §2.1.2, so it applies to trees where unpaired nodes are
+/// promoted (`OddNodeStrategy::Promote`, the default). Proving from size `0`
+/// or to the same size gives an empty proof.
+pub fn merkle_consistency_proof<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    old_size: usize,
+    hasher: &H,
+) -> Result<Vec<Hash<N>>, MerkleError> {
+    consistency_proof_from_layers(&merkle_layers(leaves, hasher), old_size)
+}
+
+pub(crate) fn consistency_proof_from_layers<const N: usize>(
+    layers: &[Vec<Leaf<N>>],
+    old_size: usize,
+) -> Result<Vec<Hash<N>>, MerkleError> {
+    let new_size = layers[0].len();
+    if old_size > new_size {
+        return Err(MerkleError::IndexOutOfRange {
+            index: old_size,
+            len: new_size,
+        });
+    }
+
+    let mut proof = vec![];
+    if old_size > 0 {
+        subproof(
+            old_size,
+            0,
+            new_size,
+            true,
+            &mut |start, n| Ok::<Hash<N>, MerkleError>(subtree_root(layers, start, n)),
+            &mut proof,
+        )?;
+    }
+
+    Ok(proof)
+}
+
+/// `SUBPROOF(m, D[start:start + n], b)` of RFC 6962
+///
+/// `subtree_root(start, n)` returns `MTH(D[start:start + n])`, so the proof can
+/// be built from the cached layers or from tiles.
+pub(crate) fn subproof<const N: usize, E>(
+    m: usize,
+    start: usize,
+    n: usize,
+    complete: bool,
+    subtree_root: &mut dyn FnMut(usize, usize) -> Result<Hash<N>, E>,
+    proof: &mut Vec<Hash<N>>,
+) -> Result<(), E> {
+    if m == n {
+        if !complete {
+            proof.push(subtree_root(start, n)?);
+        }
+        return Ok(());
+    }
+
+    let k = largest_power_of_two_below(n);
+    if m <= k {
+        subproof(m, start, k, complete, subtree_root, proof)?;
+        proof.push(subtree_root(start + k, n - k)?);
+    } else {
+        subproof(m - k, start + k, n - k, false, subtree_root, proof)?;
+        proof.push(subtree_root(start, k)?);
+    }
+
+    Ok(())
+}
+
+/// The largest power of two smaller than `n`, where RFC 6962 splits `n` leaves
+pub(crate) fn largest_power_of_two_below(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// `MTH(D[start:start + n])`, read from the cached layers
+///
+/// The ranges RFC 6962 asks for are either complete subtrees or end with the
+/// tree, so each one is a single node at the level of `n` rounded up to a
+/// power of two.
+fn subtree_root<const N: usize>(layers: &[Vec<Leaf<N>>], start: usize, n: usize) -> Hash<N> {
+    let level = n.next_power_of_two().trailing_zeros() as usize;
+
+    layers[level][start >> level]
+}
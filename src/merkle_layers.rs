@@ -0,0 +1,239 @@
+use crate::hasher::Hashable;
+use crate::node::{Node, Side};
+use crate::options::{OddNodeStrategy, TreeOptions};
+use crate::{Leaf, Proof};
+
+/// Build every layer of the tree, from the leaves (`layers[0]`) up to the root
+///
+/// An unpaired node at the end of a layer is promoted unchanged to the next one,
+/// exactly as `merkle_root` does.
+pub fn merkle_layers<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    hasher: &H,
+) -> Vec<Vec<Leaf<N>>> {
+    merkle_layers_with_options(leaves, hasher, &TreeOptions::default())
+}
+
+/// Same as `merkle_layers`, pairing unpaired nodes following `options.odd_node`
+///
+/// The leaves are taken as given, `options.sort_leaves` is not applied.
+pub fn merkle_layers_with_options<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    hasher: &H,
+    options: &TreeOptions<N>,
+) -> Vec<Vec<Leaf<N>>> {
+    let mut layers = vec![leaves.to_vec()];
+    let mut padding = Padding::new(options.odd_node);
+
+    while padding.has_parent(layers[layers.len() - 1].len()) {
+        let next_layer = up_layer(&layers[layers.len() - 1], hasher, &padding);
+        layers.push(next_layer);
+        padding.up(hasher);
+    }
+
+    layers
+}
+
+/// Sibling given to the unpaired node of a layer, following an `OddNodeStrategy`
+///
+/// Starts at the leaves, call `up` when moving to the next layer so the
+/// filler of `PadWith` becomes the root of a subtree as high as the layer.
+pub(crate) struct Padding<const N: usize> {
+    strategy: OddNodeStrategy<N>,
+    height: u32,
+}
+
+impl<const N: usize> Padding<N> {
+    pub(crate) fn new(strategy: OddNodeStrategy<N>) -> Self {
+        Padding {
+            strategy,
+            height: 0,
+        }
+    }
+
+    pub(crate) fn up<H: Hashable<N>>(&mut self, hasher: &H) {
+        if let OddNodeStrategy::PadWith { leaf, .. } = &mut self.strategy {
+            let mut buffer = [0u8; N];
+            hasher.hash_nodes(leaf, leaf, &mut buffer);
+            *leaf = buffer;
+        }
+        self.height += 1;
+    }
+
+    /// Whether a layer of `width` nodes at the current height is below the root
+    ///
+    /// A single node is the root, unless `PadWith` asks for a deeper tree.
+    pub(crate) fn has_parent(&self, width: usize) -> bool {
+        match self.strategy {
+            OddNodeStrategy::PadWith {
+                depth: Some(depth), ..
+            } => width > 1 || (width == 1 && self.height < depth),
+            _ => width > 1,
+        }
+    }
+
+    /// `None` when the node is promoted unchanged
+    pub(crate) fn sibling(&self, node: &Leaf<N>) -> Option<Leaf<N>> {
+        match self.strategy {
+            OddNodeStrategy::Promote => None,
+            OddNodeStrategy::Duplicate => Some(*node),
+            OddNodeStrategy::PadWith { leaf, .. } => Some(leaf),
+        }
+    }
+}
+
+/// Sort the leaves bytewise, optionally dropping duplicates
+///
+/// Also returns, for every original index, the position of its leaf in the
+/// sorted list.
+pub(crate) fn sort_leaves<const N: usize>(
+    leaves: &[Leaf<N>],
+    dedup: bool,
+) -> (Vec<Leaf<N>>, Vec<usize>) {
+    let mut order: Vec<usize> = (0..leaves.len()).collect();
+    order.sort_by_key(|&index| leaves[index]);
+
+    let mut sorted: Vec<Leaf<N>> = order.iter().map(|&index| leaves[index]).collect();
+    let mut positions = vec![0; leaves.len()];
+
+    if dedup {
+        sorted.dedup();
+        for (position, leaf) in positions.iter_mut().zip(leaves) {
+            *position = sorted.binary_search(leaf).unwrap_or_default();
+        }
+    } else {
+        for (sorted_index, &index) in order.iter().enumerate() {
+            positions[index] = sorted_index;
+        }
+    }
+
+    (sorted, positions)
+}
+
+pub(crate) fn up_layer<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    hasher: &H,
+    padding: &Padding<N>,
+) -> Vec<Leaf<N>> {
+    (0..leaves.len().div_ceil(2))
+        .map(|index| parent_node(leaves, index, hasher, padding))
+        .collect()
+}
+
+/// The node at `index` in the layer above `layer`
+pub(crate) fn parent_node<const N: usize, H: Hashable<N>>(
+    layer: &[Leaf<N>],
+    index: usize,
+    hasher: &H,
+    padding: &Padding<N>,
+) -> Leaf<N> {
+    let (left, right) = match &layer[index * 2..layer.len().min(index * 2 + 2)] {
+        [left, right] => (*left, *right),
+        [single] => match padding.sibling(single) {
+            Some(sibling) => (*single, sibling),
+            None => return *single,
+        },
+        _ => unreachable!(),
+    };
+
+    let mut buffer = [0u8; N];
+    hasher.hash_nodes(&left, &right, &mut buffer);
+    buffer
+}
+
+/// Recompute every node at or to the right of the leaf `index`, on every layer
+///
+/// Appending `k` leaves touches O(k + log n) nodes.
+/// Layers left above the new root are dropped.
+pub(crate) fn rebuild_layers_from<const N: usize, H: Hashable<N>>(
+    layers: &mut Vec<Vec<Leaf<N>>>,
+    mut index: usize,
+    hasher: &H,
+    odd_node: OddNodeStrategy<N>,
+) {
+    let mut level = 0;
+    let mut padding = Padding::new(odd_node);
+
+    while padding.has_parent(layers[level].len()) {
+        let parent_start = index / 2;
+        let parents: Vec<Leaf<N>> = (parent_start..layers[level].len().div_ceil(2))
+            .map(|parent| parent_node(&layers[level], parent, hasher, &padding))
+            .collect();
+
+        if level + 1 == layers.len() {
+            layers.push(Vec::with_capacity(parents.len()));
+        }
+        let next_layer = &mut layers[level + 1];
+        next_layer.truncate(parent_start);
+        next_layer.extend(parents);
+
+        index = parent_start;
+        level += 1;
+        padding.up(hasher);
+    }
+
+    layers.truncate(level + 1);
+}
+
+/// Rehash the ancestors of the leaves at `indices`, each shared ancestor once
+///
+/// `indices` must be sorted and point to existing leaves.
+pub(crate) fn rehash_paths<const N: usize, H: Hashable<N>>(
+    layers: &mut [Vec<Leaf<N>>],
+    indices: &[usize],
+    hasher: &H,
+    odd_node: OddNodeStrategy<N>,
+) {
+    let mut dirty = indices.to_vec();
+    let mut padding = Padding::new(odd_node);
+
+    for level in 0..layers.len() - 1 {
+        dirty = dirty.iter().map(|index| index / 2).collect();
+        dirty.dedup();
+
+        let (lower, upper) = layers.split_at_mut(level + 1);
+        for &parent in &dirty {
+            upper[0][parent] = parent_node(&lower[level], parent, hasher, &padding);
+        }
+
+        padding.up(hasher);
+    }
+}
+
+/// Walk the cached layers from the leaf at `index` up to the root, collecting siblings
+///
+/// An unpaired node gets the sibling chosen by `odd_node` as a right node,
+/// so `merkle_proof_check` needs no knowledge of the strategy.
+pub(crate) fn proof_from_layers<const N: usize, H: Hashable<N>>(
+    layers: &[Vec<Leaf<N>>],
+    mut index: usize,
+    hasher: &H,
+    odd_node: OddNodeStrategy<N>,
+) -> Proof<N> {
+    let mut proof: Proof<N> = Vec::with_capacity(layers.len());
+    let mut padding = Padding::new(odd_node);
+
+    for layer in &layers[..layers.len() - 1] {
+        if index % 2 == 1 {
+            proof.push(Node {
+                data: layer[index - 1],
+                side: Side::LEFT,
+            });
+        } else if index + 1 < layer.len() {
+            proof.push(Node {
+                data: layer[index + 1],
+                side: Side::RIGHT,
+            });
+        } else if let Some(sibling) = padding.sibling(&layer[index]) {
+            proof.push(Node {
+                data: sibling,
+                side: Side::RIGHT,
+            });
+        }
+
+        index /= 2;
+        padding.up(hasher);
+    }
+
+    proof
+}
@@ -0,0 +1,171 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::error::MerkleError;
+use crate::hasher::Hashable;
+use crate::merkle_layers::{merkle_layers, merkle_layers_with_options, Padding};
+use crate::merkle_multiproof_check::merkle_multiproof_check;
+use crate::options::{OddNodeStrategy, TreeOptions};
+use crate::{Hash, Leaf, HASH_LEN};
+
+/// # 🍃🍃 Proof of many leaves at once
+///
+/// The layout is the one of OpenZeppelin's `MerkleProof.multiProofVerify`:
+/// sibling hashes are deduplicated and `proof_flags` tells, for every hash to
+/// compute, whether its second operand comes from `proof` (`false`) or from
+/// the leaves and hashes already computed (`true`).
+#[derive(PartialEq, Debug, Clone)]
+pub struct MultiProof<const N: usize = HASH_LEN> {
+    /// The proven leaves, in the order the verifier consumes them
+    pub leaves: Vec<Leaf<N>>,
+    pub proof: Vec<Hash<N>>,
+    pub proof_flags: Vec<bool>,
+}
+
+/// Prove the leaves at `indices` with a single `MultiProof`
+///
+/// Like OpenZeppelin's, the layout carries no sides, so it relies on a
+/// commutative `hash_nodes`. With a plain `left || right` hasher, or when a
+/// proven node is promoted past a computed one, the leaves cannot be proven
+/// together and `MerkleError::UnsupportedMultiProof` is returned.
+pub fn merkle_multiproof<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    indices: &[usize],
+    hasher: &H,
+) -> Result<MultiProof<N>, MerkleError> {
+    multiproof_from_layers(
+        &merkle_layers(leaves, hasher),
+        indices,
+        hasher,
+        OddNodeStrategy::Promote,
+    )
+}
+
+/// Same as `merkle_multiproof`, pairing unpaired nodes following `options.odd_node`
+///
+/// The leaves are taken as given, `options.sort_leaves` is not applied.
+pub fn merkle_multiproof_with_options<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    indices: &[usize],
+    hasher: &H,
+    options: &TreeOptions<N>,
+) -> Result<MultiProof<N>, MerkleError> {
+    multiproof_from_layers(
+        &merkle_layers_with_options(leaves, hasher, options),
+        indices,
+        hasher,
+        options.odd_node,
+    )
+}
+
+pub(crate) fn multiproof_from_layers<const N: usize, H: Hashable<N>>(
+    layers: &[Vec<Leaf<N>>],
+    indices: &[usize],
+    hasher: &H,
+    odd_node: OddNodeStrategy<N>,
+) -> Result<MultiProof<N>, MerkleError> {
+    let leaves = &layers[0];
+    if leaves.is_empty() {
+        return Err(MerkleError::EmptyTree);
+    }
+    if let Some(&index) = indices.iter().find(|&&index| index >= leaves.len()) {
+        return Err(MerkleError::IndexOutOfRange {
+            index,
+            len: leaves.len(),
+        });
+    }
+
+    let top = layers.len() - 1;
+    let root = layers[top][0];
+    if indices.is_empty() {
+        return Ok(MultiProof {
+            leaves: vec![],
+            proof: vec![root],
+            proof_flags: vec![],
+        });
+    }
+
+    // every node the verifier can compute from the proven leaves
+    let mut known: Vec<BTreeSet<usize>> = vec![indices.iter().copied().collect()];
+    for level in 0..top {
+        let parents = known[level].iter().map(|index| index / 2).collect();
+        known.push(parents);
+    }
+
+    // the sibling given to the unpaired node of each layer, `None` when promoted
+    let mut padding = Padding::new(odd_node);
+    let mut odd_siblings: Vec<Option<Leaf<N>>> = Vec::with_capacity(top);
+    for layer in &layers[..top] {
+        odd_siblings.push(padding.sibling(&layer[layer.len() - 1]));
+        padding.up(hasher);
+    }
+
+    let mut queue: VecDeque<(usize, usize)> = known[0].iter().map(|&i| (0, i)).collect();
+    let mut multiproof = MultiProof {
+        leaves: known[0].iter().map(|&i| leaves[i]).collect(),
+        proof: vec![],
+        proof_flags: vec![],
+    };
+
+    while let Some((level, index)) = queue.pop_front() {
+        let (level, index) = promote(layers, &odd_siblings, level, index);
+        if level == top {
+            // the root must be the very last node computed
+            if !queue.is_empty() {
+                return Err(MerkleError::UnsupportedMultiProof);
+            }
+            break;
+        }
+
+        let sibling = index ^ 1;
+        if known[level].contains(&sibling) {
+            match queue.pop_front() {
+                Some((l, i)) if promote(layers, &odd_siblings, l, i) == (level, sibling) => {
+                    multiproof.proof_flags.push(true)
+                }
+                _ => return Err(MerkleError::UnsupportedMultiProof),
+            }
+        } else if sibling < layers[level].len() {
+            multiproof.proof.push(layers[level][sibling]);
+            multiproof.proof_flags.push(false);
+        } else {
+            multiproof
+                .proof
+                .push(odd_siblings[level].unwrap_or([0u8; N]));
+            multiproof.proof_flags.push(false);
+        }
+
+        queue.push_back((level + 1, index / 2));
+    }
+
+    // the layout has no sides, make sure this hasher does not need them
+    let computed = merkle_multiproof_check(
+        &multiproof.proof,
+        &multiproof.proof_flags,
+        &multiproof.leaves,
+        hasher,
+    )?;
+    if computed != root {
+        return Err(MerkleError::UnsupportedMultiProof);
+    }
+
+    Ok(multiproof)
+}
+
+/// Follow an unpaired node up to the first layer where it has a sibling
+fn promote<const N: usize>(
+    layers: &[Vec<Leaf<N>>],
+    odd_siblings: &[Option<Leaf<N>>],
+    mut level: usize,
+    mut index: usize,
+) -> (usize, usize) {
+    while level + 1 < layers.len()
+        && index.is_multiple_of(2)
+        && index + 1 == layers[level].len()
+        && odd_siblings[level].is_none()
+    {
+        level += 1;
+        index /= 2;
+    }
+
+    (level, index)
+}
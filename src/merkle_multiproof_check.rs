@@ -0,0 +1,57 @@
+use crate::error::MerkleError;
+use crate::hasher::Hashable;
+use crate::{Hash, Leaf, Root};
+
+/// Rebuild the root from a multiproof, as OpenZeppelin's `processMultiProof` does
+pub fn merkle_multiproof_check<const N: usize, H: Hashable<N>>(
+    proof: &[Hash<N>],
+    proof_flags: &[bool],
+    leaves: &[Leaf<N>],
+    hasher: &H,
+) -> Result<Root<N>, MerkleError> {
+    let total_hashes = proof_flags.len();
+    if leaves.len() + proof.len() != total_hashes + 1 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut hashes: Vec<Hash<N>> = Vec::with_capacity(total_hashes);
+    let mut leaf_pos = 0;
+    let mut hash_pos = 0;
+    let mut proof_pos = 0;
+
+    for &flag in proof_flags {
+        let mut next = || -> Result<Hash<N>, MerkleError> {
+            if leaf_pos < leaves.len() {
+                leaf_pos += 1;
+                Ok(leaves[leaf_pos - 1])
+            } else if hash_pos < hashes.len() {
+                hash_pos += 1;
+                Ok(hashes[hash_pos - 1])
+            } else {
+                Err(MerkleError::MalformedProof)
+            }
+        };
+
+        let a = next()?;
+        let b = if flag {
+            next()?
+        } else if proof_pos < proof.len() {
+            proof_pos += 1;
+            proof[proof_pos - 1]
+        } else {
+            return Err(MerkleError::MalformedProof);
+        };
+
+        let mut buffer = [0u8; N];
+        hasher.hash_nodes(&a, &b, &mut buffer);
+        hashes.push(buffer);
+    }
+
+    match (hashes.last(), leaves.first(), proof.first()) {
+        (Some(root), _, _) if proof_pos == proof.len() => Ok(*root),
+        (Some(_), _, _) => Err(MerkleError::MalformedProof),
+        (None, Some(leaf), _) => Ok(*leaf),
+        (None, None, Some(root)) => Ok(*root),
+        (None, None, None) => Err(MerkleError::MalformedProof),
+    }
+}
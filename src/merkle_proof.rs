@@ -1,85 +1,83 @@
+use crate::error::MerkleError;
 use crate::hasher::Hashable;
+use crate::merkle_layers::{merkle_layers_with_options, proof_from_layers};
 use crate::merkle_proof_mixed::merkle_proof_mixed_tree;
-use crate::merkle_root::merkle_root;
-use crate::node::Node;
-use crate::utils::is_power_of_two;
+use crate::options::TreeOptions;
 use crate::{Leaf, Proof};
 
-pub fn merkle_proof<H: Hashable>(leaves: &[Leaf], leaf: Leaf, hasher: &H) -> Proof {
-    let mut proof: Proof = Vec::new();
-
-    if !is_power_of_two(leaves.len() as u32) {
-        return merkle_proof_mixed_tree(leaves, leaf, hasher);
-    }
-
-    let mut current_leaves = leaves;
-    while current_leaves.len() > 1 {
-        let index = match current_leaves.iter().position(|x| x == &leaf) {
-            Some(i) => i,
-            None => panic!("Leaf does not exist in the tree"),
-        };
-        if current_leaves.len() == 2 {
-            if index == 1 {
-                // proof.push(Node(data = leaves[0], side = Side.LEFT))
-                let left: [u8; 32] = current_leaves[0]
-                    .as_slice()
-                    .try_into()
-                    .expect("Failed to convert LEFT to array");
-                proof.push(Node {
-                    data: left,
-                    side: 0.into(),
-                });
-                break;
-            } else {
-                // proof.append(Node(data = leaves[1], side = Side.RIGHT))
-                let right: [u8; 32] = current_leaves[1]
-                    .as_slice()
-                    .try_into()
-                    .expect("Failed to convert RIGHT to array");
-                proof.push(Node {
-                    data: right,
-                    side: 1.into(),
-                });
-                break;
-            }
-        }
+/// Power-of-two and mixed trees share the same layered shape, so both are
+/// proven by walking the layers from the leaf up to the root
+pub fn merkle_proof<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    leaf: Leaf<N>,
+    hasher: &H,
+) -> Result<Proof<N>, MerkleError> {
+    merkle_proof_mixed_tree(leaves, leaf, hasher)
+}
 
-        let half_size = current_leaves.len() / 2;
+/// Prove the leaf at `index`, so any copy of a duplicated leaf can be proven
+pub fn merkle_proof_at<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    index: usize,
+    hasher: &H,
+) -> Result<Proof<N>, MerkleError> {
+    merkle_proof_at_with_options(leaves, index, hasher, &TreeOptions::default())
+}
 
-        // divide a lista em 2, left e right
-        let (left, right) = current_leaves.split_at(half_size);
+/// Same as `merkle_proof_at`, pairing unpaired nodes following `options.odd_node`
+///
+/// The proof carries every sibling explicitly, so `merkle_proof_check` needs
+/// no options to verify it. The leaves are taken as given,
+/// `options.sort_leaves` is not applied.
+pub fn merkle_proof_at_with_options<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    index: usize,
+    hasher: &H,
+    options: &TreeOptions<N>,
+) -> Result<Proof<N>, MerkleError> {
+    check_index(leaves, index)?;
 
-        // se o index estiver em left
-        if index < half_size {
-            // faça a merkle root de right
-            // TODO: make root must be a async (using other thread)
-            let right_root = merkle_root(right, hasher);
+    let layers = merkle_layers_with_options(leaves, hasher, options);
+    Ok(proof_from_layers(&layers, index, hasher, options.odd_node))
+}
 
-            // faça o node passando right e 1 (direita)
-            // adicione o node na lista de prova
-            proof.push(Node {
-                data: right_root,
-                side: 1.into(),
-            });
+/// Every index that holds `leaf`, in ascending order
+pub fn leaf_indices<const N: usize>(leaves: &[Leaf<N>], leaf: Leaf<N>) -> Vec<usize> {
+    leaves
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| *x == &leaf)
+        .map(|(i, _)| i)
+        .collect()
+}
 
-            current_leaves = left;
-        } else {
-            // se o index estiver em right
-            // faça a merkle root de left
-            // TODO: make root must be a async (using other thread)
-            let left_root = merkle_root(left, hasher);
-            // faça o node passando left e 0 (esquerda)
+pub(crate) fn check_index<const N: usize>(
+    leaves: &[Leaf<N>],
+    index: usize,
+) -> Result<(), MerkleError> {
+    if leaves.is_empty() {
+        return Err(MerkleError::EmptyTree);
+    }
+    if index >= leaves.len() {
+        return Err(MerkleError::IndexOutOfRange {
+            index,
+            len: leaves.len(),
+        });
+    }
 
-            // adicione o node na lista de prova
-            proof.push(Node {
-                data: left_root,
-                side: 0.into(),
-            });
+    Ok(())
+}
 
-            current_leaves = right;
-        }
+pub(crate) fn find_leaf<const N: usize>(
+    leaves: &[Leaf<N>],
+    leaf: Leaf<N>,
+) -> Result<usize, MerkleError> {
+    if leaves.is_empty() {
+        return Err(MerkleError::EmptyTree);
     }
 
-    proof.reverse();
-    proof
+    leaves
+        .iter()
+        .position(|x| x == &leaf)
+        .ok_or(MerkleError::LeafNotFound)
 }
@@ -1,13 +1,28 @@
+use crate::error::MerkleError;
 use crate::hasher::Hashable;
-use crate::{Leaf, Proof};
+use crate::node::Side;
+use crate::{Hash, Leaf, Proof, Root};
+
+/// A tree indexed by `usize` is never deeper than this
+pub(crate) const MAX_PROOF_LEN: usize = usize::BITS as usize;
+
+/// Every sibling is carried by the proof, including the ones added by
+/// `OddNodeStrategy::Duplicate` or `PadWith`, so any strategy checks the same way
+pub fn merkle_proof_check<const N: usize, H: Hashable<N>>(
+    proof: Proof<N>,
+    leaf: Leaf<N>,
+    hasher: &H,
+) -> Result<Root<N>, MerkleError> {
+    if proof.len() > MAX_PROOF_LEN {
+        return Err(MerkleError::MalformedProof);
+    }
 
-pub fn merkle_proof_check<H: Hashable>(proof: Proof, leaf: Leaf, hasher: &H) -> Leaf {
     let mut current_hash = leaf;
 
     for node in proof {
-        let mut buffer = [0u8; 32];
+        let mut buffer = [0u8; N];
 
-        if node.side == 1.into() {
+        if node.side == Side::RIGHT {
             hasher.hash_nodes(&current_hash, &node.data, &mut buffer);
         } else {
             hasher.hash_nodes(&node.data, &current_hash, &mut buffer);
@@ -16,5 +31,35 @@ pub fn merkle_proof_check<H: Hashable>(proof: Proof, leaf: Leaf, hasher: &H) ->
         current_hash = buffer;
     }
 
-    current_hash
+    Ok(current_hash)
+}
+
+/// Check a proof without sides, as OpenZeppelin's `MerkleProof.verify` does
+///
+/// Every pair is sorted before hashing, so it matches trees built with
+/// `SortedPairHasher`.
+pub fn merkle_proof_check_sorted<const N: usize, H: Hashable<N>>(
+    proof: &[Hash<N>],
+    leaf: Leaf<N>,
+    hasher: &H,
+) -> Result<Root<N>, MerkleError> {
+    if proof.len() > MAX_PROOF_LEN {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut current_hash = leaf;
+
+    for sibling in proof {
+        let mut buffer = [0u8; N];
+
+        if &current_hash <= sibling {
+            hasher.hash_nodes(&current_hash, sibling, &mut buffer);
+        } else {
+            hasher.hash_nodes(sibling, &current_hash, &mut buffer);
+        }
+
+        current_hash = buffer;
+    }
+
+    Ok(current_hash)
 }
@@ -1,24 +1,37 @@
+use crate::error::MerkleError;
 use crate::hasher::Hashable;
-use crate::{Hash, Leaf, Root};
+use crate::merkle_layers::{sort_leaves, up_layer, Padding};
+use crate::options::TreeOptions;
+use crate::{Leaf, Root};
 
-pub fn merkle_root<H: Hashable>(leaves: &[Leaf], hasher: &H) -> Root {
-    let mut node: Hash = [0u8; 32];
-
-    let mut tmp = leaves.to_vec();
+pub fn merkle_root<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    hasher: &H,
+) -> Result<Root<N>, MerkleError> {
+    merkle_root_with_options(leaves, hasher, &TreeOptions::default())
+}
 
-    while tmp.len() > 1 {
-        let mut next_level: Vec<Leaf> = vec![];
+/// Same as `merkle_root`, following `options` as `MerkleTree` does
+pub fn merkle_root_with_options<const N: usize, H: Hashable<N>>(
+    leaves: &[Leaf<N>],
+    hasher: &H,
+    options: &TreeOptions<N>,
+) -> Result<Root<N>, MerkleError> {
+    let mut tmp: Vec<Leaf<N>> = if options.sort_leaves {
+        sort_leaves(leaves, options.dedup_leaves).0
+    } else {
+        leaves.to_vec()
+    };
 
-        for leaf_pair in tmp.chunks(2) {
-            match leaf_pair {
-                [left, right] => hasher.hash_nodes(left, right, &mut node),
-                [left] => node.copy_from_slice(left),
-                _ => unreachable!(),
-            };
-            next_level.push(node);
-        }
+    let mut padding = Padding::new(options.odd_node);
+    while padding.has_parent(tmp.len()) {
+        tmp = up_layer(&tmp, hasher, &padding);
+        padding.up(hasher);
+    }
 
-        tmp = next_level;
+    // a single leaf is its own root, unless `PadWith` asks for a deeper tree
+    match tmp.first() {
+        Some(root) => Ok(*root),
+        None => options.empty_root.root(hasher),
     }
-    node
 }
@@ -0,0 +1,732 @@
+//! # ⛰️ Merkle Mountain Range
+//!
+//! An append-only accumulator made of perfect subtrees (peaks) of decreasing
+//! height. Appending a leaf hashes at most `log n` new nodes, and the root
+//! bags the peaks from right to left: `H(p0, H(p1, H(p2, p3)))`.
+//!
+//! Nodes are numbered in post-order from `0`, so every node has a fixed
+//! position and can be stored in a flat array or file:
+//!
+//! ```text
+//!        6
+//!      /   \
+//!     2     5     9
+//!    / \   / \   / \
+//!   0   1 3   4 7   8 10
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::error::MerkleError;
+use crate::hasher::{Hashable, Keccak256Hasher};
+use crate::{Hash, Leaf, Root, HASH_LEN};
+
+/// # ❌ Errors returned by the MMR and its stores
+#[derive(Debug)]
+pub enum MmrError {
+    /// Reading or writing the store failed
+    Io(io::Error),
+    /// A proof could not be built or checked
+    Merkle(MerkleError),
+    /// The store does not hold a complete MMR
+    CorruptedStore,
+    /// No state of this MMR had that many nodes
+    InvalidSize(u64),
+}
+
+impl fmt::Display for MmrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MmrError::Io(error) => write!(f, "MMR store I/O failed: {error}"),
+            MmrError::Merkle(error) => write!(f, "{error}"),
+            MmrError::CorruptedStore => write!(f, "The MMR store is corrupted"),
+            MmrError::InvalidSize(size) => write!(f, "No state of the MMR has {size} nodes"),
+        }
+    }
+}
+
+impl std::error::Error for MmrError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MmrError::Io(error) => Some(error),
+            MmrError::Merkle(error) => Some(error),
+            MmrError::CorruptedStore | MmrError::InvalidSize(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for MmrError {
+    fn from(error: io::Error) -> Self {
+        MmrError::Io(error)
+    }
+}
+
+impl From<MerkleError> for MmrError {
+    fn from(error: MerkleError) -> Self {
+        MmrError::Merkle(error)
+    }
+}
+
+/// # 🗄️ Storage of the MMR nodes, by position
+pub trait MmrStore<const N: usize = HASH_LEN> {
+    /// Number of nodes stored
+    fn size(&self) -> u64;
+
+    /// The node at `pos`, which must be below `size`
+    fn get(&self, pos: u64) -> Result<Hash<N>, MmrError>;
+
+    /// Store `nodes` at the positions following the last one
+    fn append(&mut self, nodes: &[Hash<N>]) -> Result<(), MmrError>;
+}
+
+/// Nodes kept in a `Vec`
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStore<const N: usize = HASH_LEN> {
+    nodes: Vec<Hash<N>>,
+}
+
+impl<const N: usize> MemoryStore<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<const N: usize> MmrStore<N> for MemoryStore<N> {
+    fn size(&self) -> u64 {
+        self.nodes.len() as u64
+    }
+
+    fn get(&self, pos: u64) -> Result<Hash<N>, MmrError> {
+        usize::try_from(pos)
+            .ok()
+            .and_then(|pos| self.nodes.get(pos))
+            .copied()
+            .ok_or(MmrError::CorruptedStore)
+    }
+
+    fn append(&mut self, nodes: &[Hash<N>]) -> Result<(), MmrError> {
+        self.nodes.extend_from_slice(nodes);
+        Ok(())
+    }
+}
+
+/// Nodes stored back to back in a flat file, the node at `pos` at offset `N * pos`
+///
+/// Reads share the file cursor, so they take turns to seek and read: proofs
+/// can be made from several threads through a shared `Mmr`.
+#[derive(Debug)]
+pub struct FileStore<const N: usize = HASH_LEN> {
+    file: Mutex<File>,
+    size: u64,
+}
+
+impl<const N: usize> FileStore<N> {
+    /// Open the file at `path`, creating it if needed
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MmrError> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let len = file.metadata()?.len();
+        if !len.is_multiple_of(N as u64) {
+            return Err(MmrError::CorruptedStore);
+        }
+
+        Ok(FileStore {
+            file: Mutex::new(file),
+            size: len / N as u64,
+        })
+    }
+
+    /// Flush the appended nodes to the disk
+    pub fn sync(&self) -> Result<(), MmrError> {
+        Ok(self.file().sync_data()?)
+    }
+
+    /// The file, whose cursor is only meaningful while the guard is held
+    fn file(&self) -> MutexGuard<'_, File> {
+        // every read seeks first, a panicking reader leaves nothing to undo
+        self.file.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<const N: usize> MmrStore<N> for FileStore<N> {
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn get(&self, pos: u64) -> Result<Hash<N>, MmrError> {
+        if pos >= self.size {
+            return Err(MmrError::CorruptedStore);
+        }
+
+        let mut node = [0u8; N];
+        let mut file = self.file();
+        file.seek(SeekFrom::Start(pos * N as u64))?;
+        file.read_exact(&mut node)?;
+
+        Ok(node)
+    }
+
+    fn append(&mut self, nodes: &[Hash<N>]) -> Result<(), MmrError> {
+        let file = self.file.get_mut().unwrap_or_else(PoisonError::into_inner);
+        file.write_all(&nodes.concat())?;
+        self.size += nodes.len() as u64;
+
+        Ok(())
+    }
+}
+
+/// # 🧾 Proof that a leaf is in an MMR
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MmrProof<const N: usize = HASH_LEN> {
+    /// Number of nodes of the MMR the proof was made for
+    pub mmr_size: u64,
+    /// Siblings from the leaf up to its peak
+    pub siblings: Vec<Hash<N>>,
+    /// Every other peak, left to right
+    pub peaks: Vec<Hash<N>>,
+}
+
+/// # 🌄 Proof that an older MMR is a prefix of a newer one
+///
+/// The old peaks are nodes of the new MMR: `nodes` holds the siblings needed
+/// to climb from them to the new peaks, then the new peaks built only from
+/// new leaves, in the order the verifier consumes them.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MmrAncestryProof<const N: usize = HASH_LEN> {
+    pub old_size: u64,
+    pub new_size: u64,
+    /// Peaks of the old MMR, left to right, which bag into the old root
+    pub old_peaks: Vec<Hash<N>>,
+    pub nodes: Vec<Hash<N>>,
+}
+
+/// # ⛰️ Merkle Mountain Range
+pub struct Mmr<
+    H: Hashable<N> = Keccak256Hasher,
+    S: MmrStore<N> = MemoryStore,
+    const N: usize = HASH_LEN,
+> {
+    store: S,
+    hasher: H,
+}
+
+impl Mmr<Keccak256Hasher, MemoryStore> {
+    /// Create an empty in-memory MMR with the default Keccak256 hasher
+    pub fn new() -> Self {
+        Self::new_with_hasher(Keccak256Hasher)
+    }
+}
+
+impl Default for Mmr<Keccak256Hasher, MemoryStore> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, H: Hashable<N>> Mmr<H, MemoryStore<N>, N> {
+    /// Create an empty in-memory MMR with a custom hasher
+    pub fn new_with_hasher(hasher: H) -> Self {
+        Mmr {
+            store: MemoryStore::new(),
+            hasher,
+        }
+    }
+}
+
+impl<const N: usize, H: Hashable<N>, S: MmrStore<N>> Mmr<H, S, N> {
+    /// Use the nodes already in `store`, e.g. a `FileStore` written earlier
+    pub fn new_with_store(store: S, hasher: H) -> Result<Self, MmrError> {
+        if peak_positions(store.size()).is_none() {
+            return Err(MmrError::CorruptedStore);
+        }
+
+        Ok(Mmr { store, hasher })
+    }
+
+    /// Number of nodes, leaves and parents
+    pub fn size(&self) -> u64 {
+        self.store.size()
+    }
+
+    pub fn leaf_count(&self) -> u64 {
+        leaf_count(self.size())
+    }
+
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Append a leaf, hashing only the peaks it completes, and return its index
+    pub fn append(&mut self, leaf: Leaf<N>) -> Result<u64, MmrError> {
+        let index = self.leaf_count();
+        let mut pos = self.size();
+        let mut nodes = vec![leaf];
+        let mut height = 0;
+
+        // merge with the peaks on the left while they have the same height
+        while pos_height(pos + 1) > height {
+            pos += 1;
+            let left_pos = pos - parent_offset(height);
+            let left = self.node(left_pos, pos - nodes.len() as u64, &nodes)?;
+            let right = nodes[nodes.len() - 1];
+
+            let mut parent = [0u8; N];
+            self.hasher.hash_nodes(&left, &right, &mut parent);
+            nodes.push(parent);
+            height += 1;
+        }
+
+        self.store.append(&nodes)?;
+        Ok(index)
+    }
+
+    /// The peaks bagged from right to left, all zeros for an empty MMR
+    pub fn root(&self) -> Result<Root<N>, MmrError> {
+        let peaks = self.peaks()?;
+
+        Ok(bag_peaks(&peaks, &self.hasher))
+    }
+
+    /// Root of every perfect subtree, left to right
+    pub fn peaks(&self) -> Result<Vec<Hash<N>>, MmrError> {
+        peak_positions(self.size())
+            .ok_or(MmrError::CorruptedStore)?
+            .into_iter()
+            .map(|pos| self.store.get(pos))
+            .collect()
+    }
+
+    /// Prove the leaf at `index` against the current root
+    pub fn make_proof(&self, index: u64) -> Result<MmrProof<N>, MmrError> {
+        let leaf_count = self.leaf_count();
+        if index >= leaf_count {
+            return Err(MerkleError::IndexOutOfRange {
+                index: usize::try_from(index).unwrap_or(usize::MAX),
+                len: usize::try_from(leaf_count).unwrap_or(usize::MAX),
+            }
+            .into());
+        }
+
+        let mmr_size = self.size();
+        let peaks = peak_positions(mmr_size).ok_or(MmrError::CorruptedStore)?;
+        let mut pos = leaf_index_to_pos(index);
+        let mut height = 0;
+        let mut siblings = vec![];
+
+        while !peaks.contains(&pos) {
+            let (sibling, parent) = family(pos, height).ok_or(MmrError::CorruptedStore)?;
+            siblings.push(self.store.get(sibling)?);
+            pos = parent;
+            height += 1;
+        }
+
+        Ok(MmrProof {
+            mmr_size,
+            siblings,
+            peaks: peaks
+                .into_iter()
+                .filter(|&peak| peak != pos)
+                .map(|peak| self.store.get(peak))
+                .collect::<Result<Vec<Hash<N>>, MmrError>>()?,
+        })
+    }
+
+    /// Prove that the MMR of `old_size` nodes, an earlier state of this one, is a prefix of it
+    pub fn make_ancestry_proof(&self, old_size: u64) -> Result<MmrAncestryProof<N>, MmrError> {
+        let new_size = self.size();
+        if old_size > new_size {
+            return Err(MmrError::InvalidSize(old_size));
+        }
+        let old_peaks = peak_positions(old_size)
+            .ok_or(MmrError::InvalidSize(old_size))?
+            .into_iter()
+            .map(|pos| self.store.get(pos))
+            .collect::<Result<Vec<Hash<N>>, MmrError>>()?;
+
+        let mut nodes = vec![];
+        climb(old_size, new_size, &old_peaks, &self.hasher, &mut |pos| {
+            let node = self.store.get(pos)?;
+            nodes.push(node);
+            Ok::<Hash<N>, MmrError>(node)
+        })?;
+
+        Ok(MmrAncestryProof {
+            old_size,
+            new_size,
+            old_peaks,
+            nodes,
+        })
+    }
+
+    /// Check that the MMR with `old_root` is a prefix of this one
+    pub fn check_ancestry_proof(
+        &self,
+        proof: &MmrAncestryProof<N>,
+        old_root: Root<N>,
+    ) -> Result<(), MmrError> {
+        if proof.new_size != self.size() {
+            return Err(MerkleError::MalformedProof.into());
+        }
+        verify_ancestry_proof(proof, old_root, self.root()?, &self.hasher)?;
+
+        Ok(())
+    }
+
+    pub fn check_proof(
+        &self,
+        proof: &MmrProof<N>,
+        leaf: Leaf<N>,
+        index: u64,
+    ) -> Result<(), MmrError> {
+        verify_mmr_proof(proof, leaf, index, self.root()?, &self.hasher)?;
+
+        Ok(())
+    }
+
+    /// The node at `pos`, from `pending` when not stored yet
+    fn node(&self, pos: u64, first_pending: u64, pending: &[Hash<N>]) -> Result<Hash<N>, MmrError> {
+        match pos.checked_sub(first_pending) {
+            Some(offset) => Ok(pending[offset as usize]),
+            None => self.store.get(pos),
+        }
+    }
+}
+
+impl<const N: usize> MmrProof<N> {
+    /// Recompute the bagged root from the leaf at `index`
+    pub fn root<H: Hashable<N>>(
+        &self,
+        leaf: Leaf<N>,
+        index: u64,
+        hasher: &H,
+    ) -> Result<Root<N>, MerkleError> {
+        let peaks = peak_positions(self.mmr_size).ok_or(MerkleError::MalformedProof)?;
+        if peaks.len() != self.peaks.len() + 1 || index >= leaf_count(self.mmr_size) {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let mut pos = leaf_index_to_pos(index);
+        let mut node = leaf;
+        for (height, sibling) in self.siblings.iter().enumerate() {
+            if peaks.contains(&pos) {
+                return Err(MerkleError::MalformedProof);
+            }
+
+            let height = u32::try_from(height).map_err(|_| MerkleError::MalformedProof)?;
+            let (sibling_pos, parent) = family(pos, height).ok_or(MerkleError::MalformedProof)?;
+            let mut buffer = [0u8; N];
+            if sibling_pos < pos {
+                hasher.hash_nodes(sibling, &node, &mut buffer);
+            } else {
+                hasher.hash_nodes(&node, sibling, &mut buffer);
+            }
+            node = buffer;
+            pos = parent;
+        }
+
+        let peak = peaks
+            .iter()
+            .position(|&peak| peak == pos)
+            .ok_or(MerkleError::MalformedProof)?;
+        let mut all_peaks = self.peaks.clone();
+        all_peaks.insert(peak, node);
+
+        Ok(bag_peaks(&all_peaks, hasher))
+    }
+}
+
+/// Check that `leaf` is at `index` in the MMR with `root`
+pub fn verify_mmr_proof<const N: usize, H: Hashable<N>>(
+    proof: &MmrProof<N>,
+    leaf: Leaf<N>,
+    index: u64,
+    root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if proof.root(leaf, index, hasher)? != root {
+        return Err(MerkleError::RootMismatch);
+    }
+
+    Ok(())
+}
+
+/// Check that the MMR with `old_root` is a prefix of the MMR with `new_root`
+///
+/// Returns `MerkleError::OldRootMismatch` or `MerkleError::NewRootMismatch`
+/// when the proof is well formed but does not lead to the given root.
+pub fn verify_ancestry_proof<const N: usize, H: Hashable<N>>(
+    proof: &MmrAncestryProof<N>,
+    old_root: Root<N>,
+    new_root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if bag_peaks(&proof.old_peaks, hasher) != old_root {
+        return Err(MerkleError::OldRootMismatch);
+    }
+
+    let (new_peaks, _) = ancestry_nodes(proof, hasher)?;
+    if bag_peaks(&new_peaks, hasher) != new_root {
+        return Err(MerkleError::NewRootMismatch);
+    }
+
+    Ok(())
+}
+
+/// Turn a proof of the leaf at `index`, made against the old MMR of `ancestry`,
+/// into a proof against its new MMR
+///
+/// `ancestry.old_peaks` must be the peaks `proof` was made against, and
+/// `ancestry` should have been checked with `verify_ancestry_proof`.
+/// Nothing but the two proofs is needed.
+pub fn update_mmr_proof<const N: usize, H: Hashable<N>>(
+    proof: &MmrProof<N>,
+    index: u64,
+    ancestry: &MmrAncestryProof<N>,
+    hasher: &H,
+) -> Result<MmrProof<N>, MerkleError> {
+    let old_peak_positions =
+        peak_positions(ancestry.old_size).ok_or(MerkleError::MalformedProof)?;
+    if proof.mmr_size != ancestry.old_size || index >= leaf_count(ancestry.old_size) {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    // the old peak of the leaf, whose hash the proof does not carry
+    let mut pos = leaf_index_to_pos(index);
+    let mut height = 0;
+    while !old_peak_positions.contains(&pos) {
+        pos = family(pos, height).ok_or(MerkleError::MalformedProof)?.1;
+        height += 1;
+    }
+    let own_peak = old_peak_positions
+        .iter()
+        .position(|&peak| peak == pos)
+        .ok_or(MerkleError::MalformedProof)?;
+    let mut other_peaks = ancestry.old_peaks.clone();
+    other_peaks.remove(own_peak);
+    if other_peaks != proof.peaks || proof.siblings.len() != height as usize {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let (new_peaks, nodes) = ancestry_nodes(ancestry, hasher)?;
+    let new_peak_positions =
+        peak_positions(ancestry.new_size).ok_or(MerkleError::MalformedProof)?;
+
+    let mut siblings = proof.siblings.clone();
+    while !new_peak_positions.contains(&pos) {
+        let (sibling, parent) = family(pos, height).ok_or(MerkleError::MalformedProof)?;
+        siblings.push(*nodes.get(&sibling).ok_or(MerkleError::MalformedProof)?);
+        pos = parent;
+        height += 1;
+    }
+
+    Ok(MmrProof {
+        mmr_size: ancestry.new_size,
+        siblings,
+        peaks: new_peak_positions
+            .iter()
+            .zip(new_peaks)
+            .filter(|(&peak, _)| peak != pos)
+            .map(|(_, node)| node)
+            .collect(),
+    })
+}
+
+/// The new peaks, and every node of the new MMR known from an ancestry proof
+fn ancestry_nodes<const N: usize, H: Hashable<N>>(
+    proof: &MmrAncestryProof<N>,
+    hasher: &H,
+) -> Result<(Vec<Hash<N>>, HashMap<u64, Hash<N>>), MerkleError> {
+    let old_peaks = peak_positions(proof.old_size).ok_or(MerkleError::MalformedProof)?;
+    if proof.old_size > proof.new_size || old_peaks.len() != proof.old_peaks.len() {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    let mut nodes = proof.nodes.iter();
+    let (new_peaks, known) = climb(
+        proof.old_size,
+        proof.new_size,
+        &proof.old_peaks,
+        hasher,
+        &mut |_| nodes.next().copied().ok_or(MerkleError::MalformedProof),
+    )?;
+    if nodes.next().is_some() {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    Ok((new_peaks, known))
+}
+
+/// Climb from the old peaks to the new ones, lowest nodes first
+///
+/// `missing(pos)` provides every other node needed on the way, then the new
+/// peaks not reached from the old ones. Returns the new peaks, and every
+/// node seen by position.
+fn climb<const N: usize, H: Hashable<N>, E: From<MerkleError>>(
+    old_size: u64,
+    new_size: u64,
+    old_peaks: &[Hash<N>],
+    hasher: &H,
+    missing: &mut dyn FnMut(u64) -> Result<Hash<N>, E>,
+) -> Result<(Vec<Hash<N>>, HashMap<u64, Hash<N>>), E> {
+    let old_positions = peak_positions(old_size).ok_or(MerkleError::MalformedProof)?;
+    let new_positions = peak_positions(new_size).ok_or(MerkleError::MalformedProof)?;
+
+    let mut seen: HashMap<u64, Hash<N>> = old_positions
+        .iter()
+        .copied()
+        .zip(old_peaks.iter().copied())
+        .collect();
+    // nodes still to climb, by height then position
+    let mut pending: BTreeMap<(u32, u64), Hash<N>> = old_positions
+        .iter()
+        .zip(old_peaks)
+        .filter(|(pos, _)| !new_positions.contains(pos))
+        .map(|(&pos, &node)| ((pos_height(pos), pos), node))
+        .collect();
+
+    while let Some(((height, pos), node)) = pending.pop_first() {
+        let (sibling_pos, parent_pos) = family(pos, height).ok_or(MerkleError::MalformedProof)?;
+        if parent_pos >= new_size {
+            return Err(MerkleError::MalformedProof.into());
+        }
+
+        let sibling = match pending.remove(&(height, sibling_pos)) {
+            Some(sibling) => sibling,
+            None => missing(sibling_pos)?,
+        };
+        seen.insert(sibling_pos, sibling);
+
+        let mut parent = [0u8; N];
+        if sibling_pos < pos {
+            hasher.hash_nodes(&sibling, &node, &mut parent);
+        } else {
+            hasher.hash_nodes(&node, &sibling, &mut parent);
+        }
+        seen.insert(parent_pos, parent);
+
+        if !new_positions.contains(&parent_pos) {
+            pending.insert((height + 1, parent_pos), parent);
+        }
+    }
+
+    let mut new_peaks = Vec::with_capacity(new_positions.len());
+    for pos in new_positions {
+        let peak = match seen.get(&pos) {
+            Some(peak) => *peak,
+            None => missing(pos)?,
+        };
+        seen.insert(pos, peak);
+        new_peaks.push(peak);
+    }
+
+    Ok((new_peaks, seen))
+}
+
+/// Bag the peaks from right to left, all zeros without peaks
+pub fn bag_peaks<const N: usize, H: Hashable<N>>(peaks: &[Hash<N>], hasher: &H) -> Root<N> {
+    match peaks.split_last() {
+        Some((last, rest)) => rest.iter().rev().fold(*last, |right, left| {
+            let mut buffer = [0u8; N];
+            hasher.hash_nodes(left, &right, &mut buffer);
+            buffer
+        }),
+        None => [0u8; N],
+    }
+}
+
+/// Position of the leaf at `index`
+pub fn leaf_index_to_pos(index: u64) -> u64 {
+    2 * index - u64::from(index.count_ones())
+}
+
+/// Number of leaves of an MMR of `mmr_size` nodes
+pub fn leaf_count(mmr_size: u64) -> u64 {
+    peak_positions(mmr_size)
+        .unwrap_or_default()
+        .iter()
+        .map(|&pos| 1 << pos_height(pos))
+        .sum()
+}
+
+/// Positions of the peaks, left to right, `None` when no MMR has `mmr_size` nodes
+pub fn peak_positions(mmr_size: u64) -> Option<Vec<u64>> {
+    let mut peaks = vec![];
+    let mut start = 0;
+    let mut remaining = mmr_size;
+    let mut max_height = u64::BITS;
+
+    while remaining > 0 {
+        // the highest perfect tree, of 2^(h+1) - 1 nodes, that fits
+        let mut height = u64::BITS - 1 - remaining.leading_zeros();
+        let mut size = u64::MAX >> (u64::BITS - 1 - height);
+        if size > remaining {
+            height -= 1;
+            size >>= 1;
+        }
+        if height >= max_height {
+            return None;
+        }
+
+        peaks.push(start + size - 1);
+        start += size;
+        remaining -= size;
+        max_height = height;
+    }
+
+    Some(peaks)
+}
+
+/// Height of the node at `pos`, leaves are at height `0`
+pub(crate) fn pos_height(pos: u64) -> u32 {
+    // in 1-based post-order, the left-most node of each height is all ones,
+    // counted in u128 so the last positions do not overflow
+    let mut pos = u128::from(pos) + 1;
+    while !(pos + 1).is_power_of_two() {
+        pos -= (1 << (u128::BITS - pos.leading_zeros() - 1)) - 1;
+    }
+
+    u128::BITS - pos.leading_zeros() - 1
+}
+
+/// Distance from the left child to its parent
+fn parent_offset(height: u32) -> u64 {
+    2 << height
+}
+
+/// Distance between two siblings
+fn sibling_offset(height: u32) -> u64 {
+    (2 << height) - 1
+}
+
+/// The sibling and the parent of the node at `pos`, `None` when they cannot
+/// exist in any MMR
+pub(crate) fn family(pos: u64, height: u32) -> Option<(u64, u64)> {
+    // no MMR of at most u64::MAX nodes has a parent above height 63
+    if height >= u64::BITS - 1 {
+        return None;
+    }
+
+    let next = pos.checked_add(1)?;
+    if pos_height(next) > height {
+        // right child, its parent follows it
+        Some((pos.checked_sub(sibling_offset(height))?, next))
+    } else {
+        Some((
+            pos.checked_add(sibling_offset(height))?,
+            pos.checked_add(parent_offset(height))?,
+        ))
+    }
+}
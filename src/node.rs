@@ -1,23 +1,36 @@
-use crate::Hash;
+use crate::error::MerkleError;
+use crate::{Hash, HASH_LEN};
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum Side {
     LEFT = 0,
     RIGHT = 1,
 }
-impl From<u8> for Side {
-    fn from(num: u8) -> Self {
+impl TryFrom<u8> for Side {
+    type Error = MerkleError;
+
+    fn try_from(num: u8) -> Result<Self, Self::Error> {
         match num {
-            0 => Side::LEFT,
-            1 => Side::RIGHT,
-            _ => panic!("Invalid value for Side enum, must be either `0` or `1`"),
+            0 => Ok(Side::LEFT),
+            1 => Ok(Side::RIGHT),
+            _ => Err(MerkleError::InvalidSide(num)),
         }
     }
 }
+impl From<Side> for u8 {
+    fn from(side: Side) -> Self {
+        side as u8
+    }
+}
 
 /// # 🍃 Leaf of Tree
 #[derive(PartialEq, Debug, Clone)]
-pub struct Node {
-    pub data: Hash,
+pub struct Node<const N: usize = HASH_LEN> {
+    pub data: Hash<N>,
     pub side: Side,
 }
+
+/// Drop the sides of a proof, for trees hashed with `SortedPairHasher`
+pub fn proof_hashes<const N: usize>(proof: &[Node<N>]) -> Vec<Hash<N>> {
+    proof.iter().map(|node| node.data).collect()
+}
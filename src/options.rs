@@ -0,0 +1,75 @@
+use crate::error::MerkleError;
+use crate::hasher::Hashable;
+use crate::{Hash, Leaf, Root, HASH_LEN};
+
+/// # 🕳️ Root of a tree without leaves
+///
+/// There is no leaf to prove in an empty tree, so the proof functions return
+/// `MerkleError::EmptyTree` whatever the policy is.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum EmptyRoot {
+    /// All zeros, as `merkle_root` has always returned
+    #[default]
+    Zero,
+    /// The hash of an empty string, as in RFC 6962
+    HashOfEmpty,
+    /// Refuse to build a root, returning `MerkleError::EmptyTree`
+    Error,
+}
+
+impl EmptyRoot {
+    /// The root of an empty tree under this policy
+    pub fn root<const N: usize, H: Hashable<N>>(&self, hasher: &H) -> Result<Root<N>, MerkleError> {
+        match self {
+            EmptyRoot::Zero => Ok([0u8; N]),
+            EmptyRoot::HashOfEmpty => {
+                let mut buffer = [0u8; N];
+                hasher.hash(&[], &mut buffer);
+                Ok(buffer)
+            }
+            EmptyRoot::Error => Err(MerkleError::EmptyTree),
+        }
+    }
+}
+
+/// # ✂️ How `MerkleTree::remove` takes a leaf out of the tree
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum RemoveMode<const N: usize = HASH_LEN> {
+    /// Shift the following leaves one position to the left
+    #[default]
+    Compact,
+    /// Overwrite the leaf with this hash, every other leaf keeps its index
+    Tombstone(Hash<N>),
+}
+
+/// # 🧩 What happens to the last node of a layer with an odd number of nodes
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum OddNodeStrategy<const N: usize = HASH_LEN> {
+    /// Carry it up unchanged, as merkletreejs does
+    #[default]
+    Promote,
+    /// Hash it with itself, as Bitcoin does
+    Duplicate,
+    /// Hash it with `leaf`, or higher up with the root of a subtree of `leaf`s
+    ///
+    /// Gives the root of the leaves padded with `leaf` up to `2^depth` leaves,
+    /// without hashing the padding. The root keeps being hashed with the root
+    /// of an empty subtree up to `depth`, so the tree matches fixed-depth trees
+    /// such as the Ethereum deposit contract. With `depth: None`, or when the
+    /// leaves need more levels, the tree is as deep as its leaves need and the
+    /// padding stops at the next power of two.
+    PadWith { leaf: Leaf<N>, depth: Option<u32> },
+}
+
+/// # ⚙️ Options shared by `MerkleTree` and the `*_with_options` functions
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct TreeOptions<const N: usize = HASH_LEN> {
+    pub empty_root: EmptyRoot,
+    pub remove_mode: RemoveMode<N>,
+    pub odd_node: OddNodeStrategy<N>,
+    /// Sort the leaves bytewise before building the tree, like merkletreejs' `sortLeaves`,
+    /// so the same set of leaves always gives the same root
+    pub sort_leaves: bool,
+    /// With `sort_leaves`, also keep a single copy of each duplicated leaf
+    pub dedup_leaves: bool,
+}
@@ -0,0 +1,150 @@
+//! # 📜 RFC 6962 (Certificate Transparency) Merkle Tree Hash
+//!
+//! Leaves are hashed as `SHA-256(0x00 || data)` and nodes as
+//! `SHA-256(0x01 || left || right)`. The RFC splits `n` leaves at the largest
+//! power of two smaller than `n`, which gives exactly the shape of `MerkleTree`
+//! with `OddNodeStrategy::Promote`, so the cached layers are reused as is.
+//!
+//! Audit paths are plain `Vec<Hash>`: the side of every node is derived from
+//! the leaf index and the tree size, as in RFC 9162 §2.1.3.2.
+//!
+//! The RFC fixes the hash to SHA-256, so unlike the rest of the crate this
+//! module is not generic over the hash size: everything here is 32 bytes.
+
+use crate::error::MerkleError;
+use crate::hasher::{DomainSeparatedHasher, Hashable, Sha256Hasher};
+use crate::merkle_proof::merkle_proof_at;
+use crate::node::proof_hashes;
+use crate::options::{EmptyRoot, OddNodeStrategy, TreeOptions};
+use crate::tree::MerkleTree;
+use crate::{Hash, Leaf, Root};
+
+/// SHA-256 with the `0x00` leaf and `0x01` node prefixes
+pub type Rfc6962Hasher = DomainSeparatedHasher<Sha256Hasher>;
+
+pub fn hasher() -> Rfc6962Hasher {
+    DomainSeparatedHasher::new(Sha256Hasher)
+}
+
+/// Unpaired nodes are promoted and the empty tree hashes to `SHA-256("")`
+pub fn options() -> TreeOptions {
+    TreeOptions {
+        empty_root: EmptyRoot::HashOfEmpty,
+        odd_node: OddNodeStrategy::Promote,
+        ..Default::default()
+    }
+}
+
+/// Build the tree of the log entries `data`, hashing each one as a leaf
+pub fn tree_from_data<D: AsRef<[u8]>>(data: &[D]) -> MerkleTree<Rfc6962Hasher> {
+    MerkleTree::new_from_data(data, hasher(), options())
+        .expect("an RFC 6962 tree accepts any number of leaves")
+}
+
+/// `MTH(D[n])`, the Merkle Tree Hash of the log entries `data`
+pub fn tree_hash<D: AsRef<[u8]>>(data: &[D]) -> Root {
+    tree_from_data(data).root()
+}
+
+/// `SHA-256(0x00 || data)`
+pub fn leaf_hash(data: &[u8]) -> Leaf {
+    let mut buffer = [0u8; 32];
+    hasher().hash_leaf(data, &mut buffer);
+    buffer
+}
+
+/// `PATH(index, D[tree_size])`, the audit path of the leaf at `index`, from the leaf up
+///
+/// `D[tree_size]` is the first `tree_size` leaves of `tree`, so the path can be
+/// made for any older size. The tree must promote its unpaired nodes, as the
+/// trees of `tree_from_data` do, or `MerkleError::UnsupportedOddNodeStrategy`
+/// is returned.
+pub fn inclusion_proof(
+    tree: &MerkleTree<Rfc6962Hasher>,
+    index: usize,
+    tree_size: usize,
+) -> Result<Vec<Hash>, MerkleError> {
+    if tree.options().odd_node != OddNodeStrategy::Promote {
+        return Err(MerkleError::UnsupportedOddNodeStrategy);
+    }
+
+    let leaves = tree.leaves();
+    if tree_size > leaves.len() {
+        return Err(MerkleError::IndexOutOfRange {
+            index: tree_size,
+            len: leaves.len(),
+        });
+    }
+
+    let proof = if tree_size == leaves.len() {
+        tree.make_proof_at(index)?
+    } else {
+        merkle_proof_at(&leaves[..tree_size], index, tree.hasher())?
+    };
+
+    Ok(proof_hashes(&proof))
+}
+
+/// Recompute the root of a tree of `tree_size` leaves from an audit path
+pub fn root_from_inclusion_proof<H: Hashable>(
+    proof: &[Hash],
+    leaf: Leaf,
+    index: usize,
+    tree_size: usize,
+    hasher: &H,
+) -> Result<Root, MerkleError> {
+    if index >= tree_size {
+        return Err(MerkleError::IndexOutOfRange {
+            index,
+            len: tree_size,
+        });
+    }
+
+    let mut node_index = index;
+    let mut last_index = tree_size - 1;
+    let mut current_hash = leaf;
+
+    for sibling in proof {
+        if last_index == 0 {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let mut buffer = [0u8; 32];
+        if node_index % 2 == 1 || node_index == last_index {
+            hasher.hash_nodes(sibling, &current_hash, &mut buffer);
+            // skip the layers where this node is promoted
+            while node_index.is_multiple_of(2) && node_index != 0 {
+                node_index >>= 1;
+                last_index >>= 1;
+            }
+        } else {
+            hasher.hash_nodes(&current_hash, sibling, &mut buffer);
+        }
+        current_hash = buffer;
+
+        node_index >>= 1;
+        last_index >>= 1;
+    }
+
+    if last_index != 0 {
+        return Err(MerkleError::MalformedProof);
+    }
+
+    Ok(current_hash)
+}
+
+/// Check that the leaf at `index` is in the tree of `tree_size` leaves with `root`
+pub fn verify_inclusion<H: Hashable>(
+    proof: &[Hash],
+    leaf: Leaf,
+    index: usize,
+    tree_size: usize,
+    root: Root,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if root_from_inclusion_proof(proof, leaf, index, tree_size, hasher)? != root {
+        return Err(MerkleError::RootMismatch);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,450 @@
+//! # 🕳️ Sparse Merkle tree
+//!
+//! An authenticated key-value map: every 256-bit key has its own leaf in a
+//! tree of depth 256, where the path to a leaf is given by the bits of its
+//! key, most significant bit first. A present key holds `hash_leaf(value)`,
+//! an absent one the zero hash, and every empty subtree hashes to a
+//! precomputed default, so only the paths of present keys are stored.
+//!
+//! Keys are always 32 bytes, while nodes take the size `N` of the hasher.
+//!
+//! The same proof shows that a key holds a value (membership) or that it
+//! holds nothing (non-membership).
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::MerkleError;
+use crate::hasher::{Hashable, Keccak256Hasher};
+use crate::{Hash, Root, HASH_LEN};
+
+/// Number of levels between a leaf and the root
+pub const DEPTH: usize = 256;
+
+/// # 🧾 Proof of the leaf of a key, present or absent
+///
+/// `siblings[h]` is the sibling at height `h` of the path, from the leaf up.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SparseProof<const N: usize = HASH_LEN> {
+    pub siblings: Vec<Hash<N>>,
+}
+
+/// # 🗜️ Sparse proof without its default siblings
+///
+/// Bit `h` of `bitmap`, counted from the least significant bit of the
+/// big-endian number, is set when the sibling at height `h` is the root of an
+/// empty subtree. `siblings` holds the other ones, from the leaf up.
+/// Encoded as the 32 bytes of the bitmap followed by those hashes.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CompressedSparseProof<const N: usize = HASH_LEN> {
+    pub bitmap: Hash,
+    pub siblings: Vec<Hash<N>>,
+}
+
+/// # 🕳️ Sparse Merkle Tree of depth 256
+pub struct SparseMerkleTree<H: Hashable<N> = Keccak256Hasher, const N: usize = HASH_LEN> {
+    hasher: H,
+    /// `defaults[h]` is the root of an empty subtree of height `h`
+    defaults: Vec<Hash<N>>,
+    values: BTreeMap<Hash, Vec<u8>>,
+    /// Non-default nodes, keyed by height and by the key with the bits below that height cleared
+    nodes: HashMap<(usize, Hash), Hash<N>>,
+}
+
+impl SparseMerkleTree<Keccak256Hasher> {
+    /// Create an empty tree with the default Keccak256 hasher
+    pub fn new() -> Self {
+        Self::new_with_hasher(Keccak256Hasher)
+    }
+}
+
+impl Default for SparseMerkleTree<Keccak256Hasher> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, H: Hashable<N>> SparseMerkleTree<H, N> {
+    /// Create an empty tree with a custom hasher
+    pub fn new_with_hasher(hasher: H) -> Self {
+        SparseMerkleTree {
+            defaults: default_hashes(&hasher),
+            hasher,
+            values: BTreeMap::new(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> Root<N> {
+        self.node(DEPTH, &[0u8; 32])
+    }
+
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Number of present keys
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, key: &Hash) -> Option<&[u8]> {
+        self.values.get(key).map(Vec::as_slice)
+    }
+
+    /// Present keys with their values, in ascending key order
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash, &[u8])> {
+        self.values
+            .iter()
+            .map(|(key, value)| (key, value.as_slice()))
+    }
+
+    /// Set the value of `key`, rehashing its path, and return the new root
+    pub fn insert(&mut self, key: Hash, value: &[u8]) -> Root<N> {
+        let mut leaf = [0u8; N];
+        self.hasher.hash_leaf(value, &mut leaf);
+        self.values.insert(key, value.to_vec());
+
+        self.update_path(&key, leaf)
+    }
+
+    /// Remove `key`, resetting its leaf to empty, and return the new root
+    pub fn remove(&mut self, key: &Hash) -> Root<N> {
+        if self.values.remove(key).is_none() {
+            return self.root();
+        }
+
+        self.update_path(key, [0u8; N])
+    }
+
+    /// Prove the leaf of `key`, whether the key is present or not
+    pub fn make_proof(&self, key: &Hash) -> SparseProof<N> {
+        SparseProof {
+            siblings: (0..DEPTH)
+                .map(|height| self.node(height, &sibling_prefix(key, height)))
+                .collect(),
+        }
+    }
+
+    /// Prove the leaf of `key` without its default siblings
+    pub fn make_compressed_proof(&self, key: &Hash) -> CompressedSparseProof<N> {
+        self.make_proof(key).compress(&self.defaults)
+    }
+
+    /// `defaults()[h]` is the root of an empty subtree of height `h`
+    pub fn defaults(&self) -> &[Hash<N>] {
+        &self.defaults
+    }
+
+    /// Check that `key` holds `value` under the current root
+    pub fn check_membership(
+        &self,
+        proof: &SparseProof<N>,
+        key: &Hash,
+        value: &[u8],
+    ) -> Result<(), MerkleError> {
+        verify_membership(proof, key, value, self.root(), &self.hasher)
+    }
+
+    /// Check that `key` is absent under the current root
+    pub fn check_non_membership(
+        &self,
+        proof: &SparseProof<N>,
+        key: &Hash,
+    ) -> Result<(), MerkleError> {
+        verify_non_membership(proof, key, self.root(), &self.hasher)
+    }
+
+    fn node(&self, height: usize, prefix: &Hash) -> Hash<N> {
+        match self.nodes.get(&(height, *prefix)) {
+            Some(node) => *node,
+            None => self.defaults[height],
+        }
+    }
+
+    fn update_path(&mut self, key: &Hash, leaf: Hash<N>) -> Root<N> {
+        let mut node = leaf;
+
+        for height in 0..=DEPTH {
+            let prefix = clear_low_bits(key, height);
+            if node == self.defaults[height] {
+                self.nodes.remove(&(height, prefix));
+            } else {
+                self.nodes.insert((height, prefix), node);
+            }
+
+            if height < DEPTH {
+                let sibling = self.node(height, &sibling_prefix(key, height));
+                node = parent(&self.hasher, key, height, &node, &sibling);
+            }
+        }
+
+        node
+    }
+}
+
+impl<const N: usize> SparseProof<N> {
+    /// Recompute the root from the leaf of `key`, holding `value` or absent with `None`
+    pub fn root<H: Hashable<N>>(
+        &self,
+        key: &Hash,
+        value: Option<&[u8]>,
+        hasher: &H,
+    ) -> Result<Root<N>, MerkleError> {
+        if self.siblings.len() != DEPTH {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let mut node = [0u8; N];
+        if let Some(value) = value {
+            hasher.hash_leaf(value, &mut node);
+        }
+
+        for (height, sibling) in self.siblings.iter().enumerate() {
+            node = parent(hasher, key, height, &node, sibling);
+        }
+
+        Ok(node)
+    }
+
+    /// Drop the siblings equal to `defaults`, see `default_hashes`
+    pub fn compress(&self, defaults: &[Hash<N>]) -> CompressedSparseProof<N> {
+        let mut compressed = CompressedSparseProof {
+            bitmap: [0u8; 32],
+            siblings: vec![],
+        };
+
+        for (height, sibling) in self.siblings.iter().enumerate() {
+            if defaults.get(height) == Some(sibling) {
+                set_bit(&mut compressed.bitmap, height);
+            } else {
+                compressed.siblings.push(*sibling);
+            }
+        }
+
+        compressed
+    }
+}
+
+impl<const N: usize> CompressedSparseProof<N> {
+    /// Put the default siblings back, see `default_hashes`
+    pub fn decompress(&self, defaults: &[Hash<N>]) -> Result<SparseProof<N>, MerkleError> {
+        if defaults.len() < DEPTH || self.siblings.len() != self.non_default_count() {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let mut siblings = self.siblings.iter();
+        Ok(SparseProof {
+            siblings: (0..DEPTH)
+                .map(|height| {
+                    if bit(&self.bitmap, height) {
+                        defaults[height]
+                    } else {
+                        *siblings.next().unwrap_or(&defaults[height])
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 + N * self.siblings.len());
+        bytes.extend_from_slice(&self.bitmap);
+        for sibling in &self.siblings {
+            bytes.extend_from_slice(sibling);
+        }
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MerkleError> {
+        if bytes.len() < 32 || !(bytes.len() - 32).is_multiple_of(N) {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let (bitmap, siblings) = bytes.split_at(32);
+        let proof = CompressedSparseProof {
+            bitmap: bitmap.try_into().unwrap_or_default(),
+            siblings: siblings
+                .chunks_exact(N)
+                .map(|hash| hash.try_into().unwrap_or([0u8; N]))
+                .collect(),
+        };
+        if proof.siblings.len() != proof.non_default_count() {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        Ok(proof)
+    }
+
+    /// Recompute the root without decompressing, as a verifier would
+    ///
+    /// Default siblings are hashed up along the way, so no table is needed.
+    pub fn root<H: Hashable<N>>(
+        &self,
+        key: &Hash,
+        value: Option<&[u8]>,
+        hasher: &H,
+    ) -> Result<Root<N>, MerkleError> {
+        if self.siblings.len() != self.non_default_count() {
+            return Err(MerkleError::MalformedProof);
+        }
+
+        let mut node = [0u8; N];
+        if let Some(value) = value {
+            hasher.hash_leaf(value, &mut node);
+        }
+
+        let mut siblings = self.siblings.iter();
+        let mut default = [0u8; N];
+        for height in 0..DEPTH {
+            let sibling = if bit(&self.bitmap, height) {
+                default
+            } else {
+                *siblings.next().unwrap_or(&default)
+            };
+            node = parent(hasher, key, height, &node, &sibling);
+
+            if height + 1 < DEPTH {
+                let mut buffer = [0u8; N];
+                hasher.hash_nodes(&default, &default, &mut buffer);
+                default = buffer;
+            }
+        }
+
+        Ok(node)
+    }
+
+    fn non_default_count(&self) -> usize {
+        DEPTH
+            - self
+                .bitmap
+                .iter()
+                .map(|byte| byte.count_ones() as usize)
+                .sum::<usize>()
+    }
+}
+
+/// Check that `key` holds `value` in the tree with `root`
+pub fn verify_membership<const N: usize, H: Hashable<N>>(
+    proof: &SparseProof<N>,
+    key: &Hash,
+    value: &[u8],
+    root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if proof.root(key, Some(value), hasher)? != root {
+        return Err(MerkleError::RootMismatch);
+    }
+
+    Ok(())
+}
+
+/// Check that `key` is absent from the tree with `root`
+pub fn verify_non_membership<const N: usize, H: Hashable<N>>(
+    proof: &SparseProof<N>,
+    key: &Hash,
+    root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if proof.root(key, None, hasher)? != root {
+        return Err(MerkleError::RootMismatch);
+    }
+
+    Ok(())
+}
+
+/// Check a compressed proof that `key` holds `value` in the tree with `root`
+pub fn verify_compressed_membership<const N: usize, H: Hashable<N>>(
+    proof: &CompressedSparseProof<N>,
+    key: &Hash,
+    value: &[u8],
+    root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if proof.root(key, Some(value), hasher)? != root {
+        return Err(MerkleError::RootMismatch);
+    }
+
+    Ok(())
+}
+
+/// Check a compressed proof that `key` is absent from the tree with `root`
+pub fn verify_compressed_non_membership<const N: usize, H: Hashable<N>>(
+    proof: &CompressedSparseProof<N>,
+    key: &Hash,
+    root: Root<N>,
+    hasher: &H,
+) -> Result<(), MerkleError> {
+    if proof.root(key, None, hasher)? != root {
+        return Err(MerkleError::RootMismatch);
+    }
+
+    Ok(())
+}
+
+/// `defaults[h]` is the root of an empty subtree of height `h`
+pub fn default_hashes<const N: usize, H: Hashable<N>>(hasher: &H) -> Vec<Hash<N>> {
+    let mut defaults = Vec::with_capacity(DEPTH + 1);
+    defaults.push([0u8; N]);
+
+    for height in 0..DEPTH {
+        let mut buffer = [0u8; N];
+        hasher.hash_nodes(&defaults[height], &defaults[height], &mut buffer);
+        defaults.push(buffer);
+    }
+
+    defaults
+}
+
+/// Hash the node at `height` on the path of `key` with its sibling
+fn parent<const N: usize, H: Hashable<N>>(
+    hasher: &H,
+    key: &Hash,
+    height: usize,
+    node: &Hash<N>,
+    sibling: &Hash<N>,
+) -> Hash<N> {
+    let mut buffer = [0u8; N];
+
+    if bit(key, height) {
+        hasher.hash_nodes(sibling, node, &mut buffer);
+    } else {
+        hasher.hash_nodes(node, sibling, &mut buffer);
+    }
+
+    buffer
+}
+
+/// Bit `height` of `key`, counted from the least significant bit:
+/// whether the path goes right just below height `height + 1`
+fn bit(key: &Hash, height: usize) -> bool {
+    key[31 - height / 8] >> (height % 8) & 1 == 1
+}
+
+fn set_bit(bitmap: &mut Hash, height: usize) {
+    bitmap[31 - height / 8] |= 1 << (height % 8);
+}
+
+/// `key` with every bit below `height` cleared
+fn clear_low_bits(key: &Hash, height: usize) -> Hash {
+    let mut prefix = *key;
+
+    for (index, byte) in prefix.iter_mut().rev().enumerate() {
+        let cleared = height.saturating_sub(index * 8).min(8);
+        *byte &= (0xffu16 << cleared) as u8;
+    }
+
+    prefix
+}
+
+/// Prefix of the sibling, at `height`, of the path of `key`
+fn sibling_prefix(key: &Hash, height: usize) -> Hash {
+    let mut prefix = clear_low_bits(key, height);
+    prefix[31 - height / 8] ^= 1 << (height % 8);
+
+    prefix
+}
@@ -0,0 +1,120 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+use super::{to_usize, Note, NoteSigner, NoteVerifier, TlogError};
+use crate::merkle_consistency_check::merkle_consistency_check;
+use crate::rfc6962::{hasher, leaf_hash, verify_inclusion};
+use crate::{Hash, Root};
+
+/// # 📍 Checkpoint of a transparency log
+///
+/// The body is `<origin>\n<tree size>\n<base64 root hash>\n`, optionally
+/// followed by extension lines, as in C2SP tlog-checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Unique name of the log, e.g. `example.com/log`
+    pub origin: String,
+    pub size: u64,
+    pub root: Root,
+    pub extensions: Vec<String>,
+}
+
+impl Checkpoint {
+    pub fn body(&self) -> String {
+        let mut body = format!(
+            "{}\n{}\n{}\n",
+            self.origin,
+            self.size,
+            BASE64.encode(self.root)
+        );
+        for extension in &self.extensions {
+            body.push_str(extension);
+            body.push('\n');
+        }
+
+        body
+    }
+
+    pub fn parse(body: &str) -> Result<Self, TlogError> {
+        let lines = body
+            .strip_suffix('\n')
+            .ok_or(TlogError::MalformedCheckpoint)?;
+        let mut lines = lines.split('\n');
+
+        let (Some(origin), Some(size), Some(root)) = (lines.next(), lines.next(), lines.next())
+        else {
+            return Err(TlogError::MalformedCheckpoint);
+        };
+
+        let size: u64 = size.parse().map_err(|_| TlogError::MalformedCheckpoint)?;
+        let root = BASE64
+            .decode(root)
+            .map_err(|_| TlogError::MalformedCheckpoint)?;
+        let extensions: Vec<String> = lines.map(str::to_string).collect();
+
+        let checkpoint = Checkpoint {
+            origin: origin.to_string(),
+            size,
+            root: root
+                .try_into()
+                .map_err(|_| TlogError::MalformedCheckpoint)?,
+            extensions,
+        };
+        // only one encoding of each checkpoint is accepted
+        if origin.is_empty() || checkpoint.body() != body {
+            return Err(TlogError::MalformedCheckpoint);
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// The signed note of this checkpoint
+    pub fn sign(&self, signer: &NoteSigner) -> Result<Note, TlogError> {
+        Note::sign(&self.body(), &[signer])
+    }
+
+    /// Parse a signed checkpoint, checking that `verifier` signed it
+    pub fn open(note: &str, verifier: &NoteVerifier) -> Result<Self, TlogError> {
+        let note = Note::parse(note)?;
+        note.verify(verifier)?;
+
+        Checkpoint::parse(&note.text)
+    }
+
+    /// Check that `entry` is at `index` in the tree of this checkpoint
+    pub fn verify_inclusion(
+        &self,
+        entry: &[u8],
+        index: u64,
+        proof: &[Hash],
+    ) -> Result<(), TlogError> {
+        verify_inclusion(
+            proof,
+            leaf_hash(entry),
+            to_usize(index)?,
+            to_usize(self.size)?,
+            self.root,
+            &hasher(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Check that the tree of this checkpoint is a prefix of the tree of `newer`
+    pub fn verify_consistency(&self, newer: &Checkpoint, proof: &[Hash]) -> Result<(), TlogError> {
+        if self.origin != newer.origin {
+            return Err(TlogError::OriginMismatch);
+        }
+
+        merkle_consistency_check(
+            proof,
+            to_usize(self.size)?,
+            to_usize(newer.size)?,
+            self.root,
+            newer.root,
+            &hasher(),
+        )?;
+
+        Ok(())
+    }
+}
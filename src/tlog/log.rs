@@ -0,0 +1,190 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+use super::{to_usize, write_atomic, Checkpoint, NoteSigner, TlogError};
+use crate::error::MerkleError;
+use crate::merkle_consistency_proof::merkle_consistency_proof;
+use crate::merkle_root::merkle_root_with_options;
+use crate::rfc6962::{self, hasher, inclusion_proof, leaf_hash, Rfc6962Hasher};
+use crate::tree::MerkleTree;
+use crate::{Hash, Leaf, Root};
+
+/// One base64 entry per line
+const ENTRIES_FILE: &str = "entries";
+/// The last signed checkpoint, as a note
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+/// # 🪵 Append-only log stored in a directory
+///
+/// Entries are appended to the `entries` file and the last signed checkpoint
+/// is kept in the `checkpoint` file. Opening the log checks the entries
+/// against that checkpoint, so any rewrite of the history is detected.
+pub struct Log {
+    dir: PathBuf,
+    origin: String,
+    signer: NoteSigner,
+    entries: Vec<Vec<u8>>,
+    tree: MerkleTree<Rfc6962Hasher>,
+    file: File,
+}
+
+impl Log {
+    /// Create an empty log in `dir` and sign its first checkpoint
+    ///
+    /// Fails if `dir` already holds a log.
+    pub fn create<P: AsRef<Path>>(
+        dir: P,
+        origin: &str,
+        signer: NoteSigner,
+    ) -> Result<Self, TlogError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(true)
+            .open(dir.join(ENTRIES_FILE))?;
+
+        let mut log = Log {
+            dir,
+            origin: origin.to_string(),
+            signer,
+            entries: vec![],
+            tree: rfc6962::tree_from_data::<&[u8]>(&[]),
+            file,
+        };
+        log.checkpoint()?;
+
+        Ok(log)
+    }
+
+    /// Open the log in `dir`, checking its entries against the last checkpoint
+    pub fn open<P: AsRef<Path>>(dir: P, signer: NoteSigner) -> Result<Self, TlogError> {
+        let dir = dir.as_ref().to_path_buf();
+        let note = fs::read_to_string(dir.join(CHECKPOINT_FILE))?;
+        let checkpoint = Checkpoint::open(&note, &signer.verifier())?;
+
+        let entries = read_entries(&dir.join(ENTRIES_FILE))?;
+        let tree = rfc6962::tree_from_data(&entries);
+
+        let size = to_usize(checkpoint.size).map_err(|_| TlogError::CheckpointMismatch)?;
+        if size > tree.leaves().len() || root_at(tree.leaves(), size) != checkpoint.root {
+            return Err(TlogError::CheckpointMismatch);
+        }
+
+        let file = OpenOptions::new()
+            .append(true)
+            .open(dir.join(ENTRIES_FILE))?;
+
+        Ok(Log {
+            dir,
+            origin: checkpoint.origin,
+            signer,
+            entries,
+            tree,
+            file,
+        })
+    }
+
+    pub fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn size(&self) -> u64 {
+        self.entries.len() as u64
+    }
+
+    pub fn root(&self) -> Root {
+        self.tree.root()
+    }
+
+    pub fn entry(&self, index: u64) -> Option<&[u8]> {
+        let index = to_usize(index).ok()?;
+        self.entries.get(index).map(Vec::as_slice)
+    }
+
+    /// Append an entry and return its index
+    ///
+    /// The entry is covered by the next `checkpoint`.
+    pub fn append(&mut self, entry: &[u8]) -> Result<u64, TlogError> {
+        writeln!(self.file, "{}", BASE64.encode(entry))?;
+
+        self.tree.push(leaf_hash(entry));
+        self.entries.push(entry.to_vec());
+
+        Ok(self.size() - 1)
+    }
+
+    /// Sign a checkpoint of the current tree, store it and return the signed note
+    pub fn checkpoint(&mut self) -> Result<String, TlogError> {
+        // never sign entries that may not be on disk yet
+        self.file.sync_data()?;
+
+        let checkpoint = Checkpoint {
+            origin: self.origin.clone(),
+            size: self.size(),
+            root: self.root(),
+            extensions: vec![],
+        };
+        let note = checkpoint.sign(&self.signer)?.to_string();
+
+        write_atomic(&self.dir.join(CHECKPOINT_FILE), note.as_bytes())?;
+
+        Ok(note)
+    }
+
+    /// Prove the entry at `index` in the tree of the first `tree_size` entries
+    pub fn inclusion_proof(&self, index: u64, tree_size: u64) -> Result<Vec<Hash>, TlogError> {
+        let (index, tree_size) = (to_usize(index)?, self.check_size(tree_size)?);
+
+        Ok(inclusion_proof(&self.tree, index, tree_size)?)
+    }
+
+    /// Prove that the tree of `old_size` entries is a prefix of the tree of `new_size` entries
+    pub fn consistency_proof(&self, old_size: u64, new_size: u64) -> Result<Vec<Hash>, TlogError> {
+        let (old_size, new_size) = (to_usize(old_size)?, self.check_size(new_size)?);
+
+        if new_size == self.entries.len() {
+            return Ok(self.tree.make_consistency_proof(old_size)?);
+        }
+
+        Ok(merkle_consistency_proof(
+            &self.tree.leaves()[..new_size],
+            old_size,
+            &hasher(),
+        )?)
+    }
+
+    fn check_size(&self, tree_size: u64) -> Result<usize, TlogError> {
+        let tree_size = to_usize(tree_size)?;
+        if tree_size > self.entries.len() {
+            return Err(MerkleError::IndexOutOfRange {
+                index: tree_size,
+                len: self.entries.len(),
+            }
+            .into());
+        }
+
+        Ok(tree_size)
+    }
+}
+
+fn read_entries(path: &Path) -> Result<Vec<Vec<u8>>, TlogError> {
+    let content = fs::read_to_string(path)?;
+    if !content.is_empty() && !content.ends_with('\n') {
+        return Err(TlogError::MalformedEntries);
+    }
+
+    content
+        .lines()
+        .map(|line| BASE64.decode(line).map_err(|_| TlogError::MalformedEntries))
+        .collect()
+}
+
+fn root_at(leaves: &[Leaf], size: usize) -> Root {
+    merkle_root_with_options(&leaves[..size], &hasher(), &rfc6962::options())
+        .expect("an RFC 6962 tree accepts any number of leaves")
+}
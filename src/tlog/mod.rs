@@ -0,0 +1,115 @@
+//! # 🪵 Transparency log
+//!
+//! A tamper-evident, append-only log of entries stored in local files.
+//! The log is an RFC 6962 tree (see `rfc6962`), its state is published as
+//! [C2SP tlog-checkpoint](https://c2sp.org/tlog-checkpoint) checkpoints signed
+//! with Ed25519 in the [signed note](https://c2sp.org/signed-note) format.
+//!
+//! Enabled by the `tlog` feature.
+
+mod checkpoint;
+mod log;
+mod note;
+mod tiles;
+
+pub use checkpoint::Checkpoint;
+pub use log::Log;
+pub use note::{Note, NoteSignature, NoteSigner, NoteVerifier};
+pub use tiles::{
+    DirectoryFetcher, Tile, TileFetcher, TileReader, TileWriter, TILE_HEIGHT, TILE_WIDTH,
+};
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::error::MerkleError;
+
+/// # ❌ Errors returned by the transparency log
+#[derive(Debug)]
+pub enum TlogError {
+    /// Reading or writing the log files failed
+    Io(io::Error),
+    /// A proof could not be built or checked
+    Merkle(MerkleError),
+    /// The text is not a signed note
+    MalformedNote,
+    /// The key is not a valid Ed25519 note key
+    MalformedKey,
+    /// The note text is not a checkpoint
+    MalformedCheckpoint,
+    /// The entries file cannot be read back
+    MalformedEntries,
+    /// The note carries no valid signature from the expected key
+    InvalidSignature,
+    /// The checkpoints come from different logs
+    OriginMismatch,
+    /// The entries on disk do not match the last signed checkpoint
+    CheckpointMismatch,
+    /// The tile does not hold as many hashes as its width
+    MalformedTile,
+    /// The size or index does not fit in a `usize` on this target
+    SizeOutOfRange(u64),
+}
+
+impl fmt::Display for TlogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TlogError::Io(error) => write!(f, "Log I/O failed: {error}"),
+            TlogError::Merkle(error) => write!(f, "{error}"),
+            TlogError::MalformedNote => write!(f, "The note is malformed"),
+            TlogError::MalformedKey => write!(f, "The note key is malformed"),
+            TlogError::MalformedCheckpoint => write!(f, "The checkpoint is malformed"),
+            TlogError::MalformedEntries => write!(f, "The entries file is malformed"),
+            TlogError::InvalidSignature => {
+                write!(f, "The note has no valid signature from the expected key")
+            }
+            TlogError::OriginMismatch => write!(f, "The checkpoints come from different logs"),
+            TlogError::CheckpointMismatch => {
+                write!(f, "The log entries do not match its last signed checkpoint")
+            }
+            TlogError::MalformedTile => write!(f, "The tile is malformed"),
+            TlogError::SizeOutOfRange(value) => {
+                write!(f, "{value} does not fit in memory on this target")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TlogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TlogError::Io(error) => Some(error),
+            TlogError::Merkle(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TlogError {
+    fn from(error: io::Error) -> Self {
+        TlogError::Io(error)
+    }
+}
+
+impl From<MerkleError> for TlogError {
+    fn from(error: MerkleError) -> Self {
+        TlogError::Merkle(error)
+    }
+}
+
+/// Write `bytes` to a temporary file next to `path`, then rename it over `path`,
+/// so readers see either the old content or the new one, never a torn write
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(bytes)?;
+    file.sync_data()?;
+    fs::rename(tmp, path)
+}
+
+/// A size or an index as a `usize`, which fails on 32-bit targets above 4 GiB entries
+pub(crate) fn to_usize(value: u64) -> Result<usize, TlogError> {
+    usize::try_from(value).map_err(|_| TlogError::SizeOutOfRange(value))
+}
@@ -0,0 +1,311 @@
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use super::TlogError;
+use crate::hasher::{Hashable, Sha256Hasher};
+
+/// Algorithm byte of Ed25519 keys in the signed note format
+const ALG_ED25519: u8 = 0x01;
+/// Every signature line starts with an em dash and a space
+const SIGNATURE_PREFIX: &str = "\u{2014} ";
+const PRIVATE_KEY_PREFIX: &str = "PRIVATE+KEY+";
+
+/// Ed25519 key that signs notes under a name, usually the log origin
+pub struct NoteSigner {
+    name: String,
+    key_id: u32,
+    key: SigningKey,
+}
+
+/// Public half of a `NoteSigner`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteVerifier {
+    name: String,
+    key_id: u32,
+    key: VerifyingKey,
+}
+
+/// A signature line of a note
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NoteSignature {
+    pub name: String,
+    pub key_id: u32,
+    pub signature: Vec<u8>,
+}
+
+/// # ✍️ Signed note
+///
+/// The text, which ends with a newline, then a blank line and one
+/// `— <name> <base64(key id || signature)>` line per signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+    pub text: String,
+    pub signatures: Vec<NoteSignature>,
+}
+
+impl NoteSigner {
+    /// Create a signer from the 32-byte Ed25519 seed
+    pub fn new(name: &str, seed: [u8; 32]) -> Result<Self, TlogError> {
+        check_name(name)?;
+
+        let key = SigningKey::from_bytes(&seed);
+        Ok(NoteSigner {
+            name: name.to_string(),
+            key_id: key_id(name, key.verifying_key().as_bytes()),
+            key,
+        })
+    }
+
+    /// Parse a `PRIVATE+KEY+<name>+<key id>+<base64 key>` string
+    pub fn from_private_key(skey: &str) -> Result<Self, TlogError> {
+        let rest = skey
+            .strip_prefix(PRIVATE_KEY_PREFIX)
+            .ok_or(TlogError::MalformedKey)?;
+        let (name, key_id, seed) = parse_key(rest)?;
+
+        let signer = NoteSigner::new(name, seed)?;
+        if signer.key_id != key_id {
+            return Err(TlogError::MalformedKey);
+        }
+
+        Ok(signer)
+    }
+
+    /// The `PRIVATE+KEY+...` string of this signer, keep it secret
+    pub fn private_key(&self) -> String {
+        format!(
+            "{PRIVATE_KEY_PREFIX}{}",
+            format_key(&self.name, self.key_id, self.key.as_bytes())
+        )
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    pub fn verifier(&self) -> NoteVerifier {
+        NoteVerifier {
+            name: self.name.clone(),
+            key_id: self.key_id,
+            key: self.key.verifying_key(),
+        }
+    }
+
+    fn sign(&self, text: &str) -> NoteSignature {
+        NoteSignature {
+            name: self.name.clone(),
+            key_id: self.key_id,
+            signature: self.key.sign(text.as_bytes()).to_bytes().to_vec(),
+        }
+    }
+}
+
+/// The private key is never printed
+impl fmt::Debug for NoteSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NoteSigner")
+            .field("name", &self.name)
+            .field("key_id", &format_args!("{:08x}", self.key_id))
+            .finish_non_exhaustive()
+    }
+}
+
+impl NoteVerifier {
+    /// Create a verifier from the 32-byte Ed25519 public key
+    pub fn new(name: &str, public_key: [u8; 32]) -> Result<Self, TlogError> {
+        check_name(name)?;
+
+        Ok(NoteVerifier {
+            name: name.to_string(),
+            key_id: key_id(name, &public_key),
+            key: VerifyingKey::from_bytes(&public_key).map_err(|_| TlogError::MalformedKey)?,
+        })
+    }
+
+    /// Parse a `<name>+<key id>+<base64 key>` string
+    pub fn from_public_key(vkey: &str) -> Result<Self, TlogError> {
+        let (name, key_id, public_key) = parse_key(vkey)?;
+
+        let verifier = NoteVerifier::new(name, public_key)?;
+        if verifier.key_id != key_id {
+            return Err(TlogError::MalformedKey);
+        }
+
+        Ok(verifier)
+    }
+
+    /// The `<name>+<key id>+<base64 key>` string to hand out to verifiers
+    pub fn public_key(&self) -> String {
+        format_key(&self.name, self.key_id, self.key.as_bytes())
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    fn verify(&self, text: &str, signature: &NoteSignature) -> bool {
+        if signature.name != self.name || signature.key_id != self.key_id {
+            return false;
+        }
+
+        match Signature::from_slice(&signature.signature) {
+            Ok(signature) => self.key.verify(text.as_bytes(), &signature).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+impl Note {
+    /// Sign `text`, which must end with a newline, with every signer
+    pub fn sign(text: &str, signers: &[&NoteSigner]) -> Result<Self, TlogError> {
+        check_text(text)?;
+
+        Ok(Note {
+            text: text.to_string(),
+            signatures: signers.iter().map(|signer| signer.sign(text)).collect(),
+        })
+    }
+
+    pub fn parse(note: &str) -> Result<Self, TlogError> {
+        let split = note.rfind("\n\n").ok_or(TlogError::MalformedNote)?;
+        let (text, lines) = (&note[..=split], &note[split + 2..]);
+        check_text(text)?;
+
+        if lines.is_empty() || !lines.ends_with('\n') {
+            return Err(TlogError::MalformedNote);
+        }
+
+        let signatures = lines[..lines.len() - 1]
+            .split('\n')
+            .map(parse_signature)
+            .collect::<Result<Vec<NoteSignature>, TlogError>>()?;
+
+        Ok(Note {
+            text: text.to_string(),
+            signatures,
+        })
+    }
+
+    /// Check that the note carries a valid signature from `verifier`
+    ///
+    /// Signatures from other keys are ignored.
+    pub fn verify(&self, verifier: &NoteVerifier) -> Result<(), TlogError> {
+        if !self
+            .signatures
+            .iter()
+            .any(|signature| verifier.verify(&self.text, signature))
+        {
+            return Err(TlogError::InvalidSignature);
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.text)?;
+
+        for signature in &self.signatures {
+            let mut data = signature.key_id.to_be_bytes().to_vec();
+            data.extend_from_slice(&signature.signature);
+
+            writeln!(
+                f,
+                "{SIGNATURE_PREFIX}{} {}",
+                signature.name,
+                BASE64.encode(data)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// First 4 bytes of `SHA-256(name || "\n" || 0x01 || public key)`
+fn key_id(name: &str, public_key: &[u8; 32]) -> u32 {
+    let mut data = name.as_bytes().to_vec();
+    data.push(b'\n');
+    data.push(ALG_ED25519);
+    data.extend_from_slice(public_key);
+
+    let mut buffer = [0u8; 32];
+    Sha256Hasher.hash(&data, &mut buffer);
+    u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]])
+}
+
+fn check_name(name: &str) -> Result<(), TlogError> {
+    if name.is_empty() || name.contains('+') || name.contains(char::is_whitespace) {
+        return Err(TlogError::MalformedKey);
+    }
+
+    Ok(())
+}
+
+fn check_text(text: &str) -> Result<(), TlogError> {
+    if !text.ends_with('\n') {
+        return Err(TlogError::MalformedNote);
+    }
+
+    Ok(())
+}
+
+fn format_key(name: &str, key_id: u32, key: &[u8; 32]) -> String {
+    let mut data = vec![ALG_ED25519];
+    data.extend_from_slice(key);
+
+    format!("{name}+{key_id:08x}+{}", BASE64.encode(data))
+}
+
+/// Split `<name>+<key id>+<base64 key>`, the base64 key may contain `+` too
+fn parse_key(key: &str) -> Result<(&str, u32, [u8; 32]), TlogError> {
+    let mut parts = key.splitn(3, '+');
+    let (Some(name), Some(key_id), Some(data)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(TlogError::MalformedKey);
+    };
+
+    if key_id.len() != 8 {
+        return Err(TlogError::MalformedKey);
+    }
+    let key_id = u32::from_str_radix(key_id, 16).map_err(|_| TlogError::MalformedKey)?;
+
+    let data = BASE64.decode(data).map_err(|_| TlogError::MalformedKey)?;
+    match data.split_first() {
+        Some((&ALG_ED25519, key)) => Ok((
+            name,
+            key_id,
+            key.try_into().map_err(|_| TlogError::MalformedKey)?,
+        )),
+        _ => Err(TlogError::MalformedKey),
+    }
+}
+
+fn parse_signature(line: &str) -> Result<NoteSignature, TlogError> {
+    let (name, data) = line
+        .strip_prefix(SIGNATURE_PREFIX)
+        .and_then(|line| line.split_once(' '))
+        .ok_or(TlogError::MalformedNote)?;
+    check_name(name).map_err(|_| TlogError::MalformedNote)?;
+
+    let data = BASE64.decode(data).map_err(|_| TlogError::MalformedNote)?;
+    if data.len() < 5 {
+        return Err(TlogError::MalformedNote);
+    }
+
+    Ok(NoteSignature {
+        name: name.to_string(),
+        key_id: u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+        signature: data[4..].to_vec(),
+    })
+}
@@ -0,0 +1,439 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::{to_usize, write_atomic, Checkpoint, NoteSigner, TlogError};
+use crate::error::MerkleError;
+use crate::hasher::Hashable;
+use crate::merkle_consistency_proof::{largest_power_of_two_below, subproof};
+use crate::mmr::bag_peaks;
+use crate::options::EmptyRoot;
+use crate::rfc6962::hasher;
+use crate::{Hash, Leaf, Root};
+
+/// Every tile spans 8 levels of the tree
+pub const TILE_HEIGHT: u32 = 8;
+/// A full tile holds 256 hashes
+pub const TILE_WIDTH: usize = 1 << TILE_HEIGHT;
+
+/// # 🧱 Tile of a tiled tree, as in C2SP tlog-tiles
+///
+/// The tile `index` at `level` holds the hashes of the nodes
+/// `index * 256 .. index * 256 + width` at tree level `8 * level`.
+/// Only complete subtrees are stored, a tile with less than 256 hashes is partial.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tile {
+    pub level: u8,
+    pub index: u64,
+    pub width: u16,
+}
+
+impl Tile {
+    /// `tile/<level>/<index>[.p/<width>]`, the index split in groups of three digits
+    /// such as `x001/x234/067`
+    pub fn path(&self) -> String {
+        let mut n = self.index;
+        let mut index = format!("{:03}", n % 1000);
+        while n >= 1000 {
+            n /= 1000;
+            index = format!("x{:03}/{index}", n % 1000);
+        }
+
+        let mut path = format!("tile/{}/{index}", self.level);
+        if usize::from(self.width) < TILE_WIDTH {
+            path.push_str(&format!(".p/{}", self.width));
+        }
+
+        path
+    }
+
+    /// The tile at `level` that holds the node `index` of tree level `8 * level`,
+    /// in a tree of `size` leaves
+    fn containing(level: u8, index: usize, size: usize) -> Tile {
+        let tile = index / TILE_WIDTH;
+        let count = size >> (TILE_HEIGHT * u32::from(level));
+
+        Tile {
+            level,
+            index: tile as u64,
+            width: (count - tile * TILE_WIDTH).min(TILE_WIDTH) as u16,
+        }
+    }
+}
+
+/// # 📥 Source of tiles, a local directory or a remote log
+pub trait TileFetcher {
+    /// The `32 * tile.width` bytes of hashes of `tile`
+    ///
+    /// Returning the full tile instead of a partial one is fine.
+    fn fetch_tile(&self, tile: &Tile) -> Result<Vec<u8>, TlogError>;
+}
+
+/// Read tiles from the directory a `TileWriter` writes to
+#[derive(Clone, Debug)]
+pub struct DirectoryFetcher {
+    dir: PathBuf,
+}
+
+impl DirectoryFetcher {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        DirectoryFetcher {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl TileFetcher for DirectoryFetcher {
+    fn fetch_tile(&self, tile: &Tile) -> Result<Vec<u8>, TlogError> {
+        match fs::read(self.dir.join(tile.path())) {
+            // partial tiles are dropped once the full tile is written
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                let full = Tile {
+                    width: TILE_WIDTH as u16,
+                    ..*tile
+                };
+                Ok(fs::read(self.dir.join(full.path()))?)
+            }
+            result => Ok(result?),
+        }
+    }
+}
+
+/// # ✏️ Tiled storage of an RFC 6962 tree
+///
+/// Only the right edge of the tree, at most 256 hashes per tile level, is
+/// kept in memory. Full tiles are written as soon as they fill up, partial
+/// ones on `flush`. Entries themselves are not stored, only their leaf hashes.
+#[derive(Debug)]
+pub struct TileWriter {
+    dir: PathBuf,
+    size: usize,
+    /// Hashes of the last, incomplete tile of every level
+    edges: Vec<Vec<Hash>>,
+}
+
+impl TileWriter {
+    /// Start an empty tree in `dir`
+    pub fn create<P: AsRef<Path>>(dir: P) -> Result<Self, TlogError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        Ok(TileWriter {
+            dir,
+            size: 0,
+            edges: vec![vec![]],
+        })
+    }
+
+    /// Resume the tree of `size` leaves in `dir`, reading back its right edge
+    ///
+    /// The tiles must have been flushed at that size.
+    pub fn open<P: AsRef<Path>>(dir: P, size: u64) -> Result<Self, TlogError> {
+        let mut writer = TileWriter::create(dir)?;
+        writer.size = to_usize(size)?;
+
+        let fetcher = DirectoryFetcher::new(&writer.dir);
+        writer.edges.clear();
+        let mut level = 0;
+        loop {
+            let count = writer.size >> (TILE_HEIGHT * u32::from(level));
+            let tile = Tile::containing(level, count - count % TILE_WIDTH, writer.size);
+            let edge = match tile.width {
+                0 => vec![],
+                _ => read_hashes(&fetcher, &tile)?,
+            };
+            writer.edges.push(edge);
+
+            if count < TILE_WIDTH {
+                break;
+            }
+            level += 1;
+        }
+
+        Ok(writer)
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size as u64
+    }
+
+    /// Append the hash of a leaf, writing every tile it fills up
+    pub fn append(&mut self, leaf: Leaf) -> Result<(), TlogError> {
+        self.size += 1;
+
+        let mut hash = leaf;
+        let mut level = 0;
+        loop {
+            if level == self.edges.len() {
+                self.edges.push(vec![]);
+            }
+            self.edges[level].push(hash);
+            if self.edges[level].len() < TILE_WIDTH {
+                break;
+            }
+
+            let count = self.size >> (TILE_HEIGHT * level as u32);
+            let tile = Tile {
+                level: level as u8,
+                index: (count / TILE_WIDTH - 1) as u64,
+                width: TILE_WIDTH as u16,
+            };
+            self.write_tile(&tile, &self.edges[level])?;
+
+            // the partial versions of this tile are obsolete
+            let partial = self.dir.join(format!("{}.p", tile.path()));
+            if partial.exists() {
+                fs::remove_dir_all(partial)?;
+            }
+
+            hash = fold(&self.edges[level]);
+            self.edges[level].clear();
+            level += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Write the partial tiles of the right edge, so readers see the current size
+    pub fn flush(&self) -> Result<(), TlogError> {
+        for (level, edge) in self.edges.iter().enumerate() {
+            if edge.is_empty() {
+                continue;
+            }
+
+            let count = self.size >> (TILE_HEIGHT * level as u32);
+            let tile = Tile::containing(level as u8, count - edge.len(), self.size);
+            self.write_tile(&tile, edge)?;
+        }
+
+        Ok(())
+    }
+
+    /// The root of the tree, computed from the right edge only
+    pub fn root(&self) -> Root {
+        // complete subtrees from left to right, largest first
+        let mut subtrees: Vec<Hash> = vec![];
+        for edge in self.edges.iter().rev() {
+            let mut start = 0;
+            for bit in (0..TILE_HEIGHT).rev() {
+                let width = 1 << bit;
+                if edge.len() & width != 0 {
+                    subtrees.push(fold(&edge[start..start + width]));
+                    start += width;
+                }
+            }
+        }
+
+        fold_right(&subtrees)
+    }
+
+    /// Flush the tiles, then sign a checkpoint of the tree and store it next to them
+    pub fn checkpoint(&self, origin: &str, signer: &NoteSigner) -> Result<String, TlogError> {
+        self.flush()?;
+
+        let checkpoint = Checkpoint {
+            origin: origin.to_string(),
+            size: self.size(),
+            root: self.root(),
+            extensions: vec![],
+        };
+        let note = checkpoint.sign(signer)?.to_string();
+        write_atomic(&self.dir.join("checkpoint"), note.as_bytes())?;
+
+        Ok(note)
+    }
+
+    fn write_tile(&self, tile: &Tile, hashes: &[Hash]) -> Result<(), TlogError> {
+        let path = self.dir.join(tile.path());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        Ok(write_atomic(&path, &hashes.concat())?)
+    }
+}
+
+/// # 🔎 Proofs of a tiled tree, reading only the tiles they need
+///
+/// `size` is the size the tiles were written for, usually the one of the
+/// latest checkpoint. Proofs about any smaller tree are served from the same tiles.
+#[derive(Clone, Debug)]
+pub struct TileReader<F: TileFetcher> {
+    fetcher: F,
+    size: u64,
+}
+
+/// Tiles already fetched while building a proof
+type TileCache = HashMap<Tile, Vec<Hash>>;
+
+impl<F: TileFetcher> TileReader<F> {
+    pub fn new(fetcher: F, size: u64) -> Self {
+        TileReader { fetcher, size }
+    }
+
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
+    pub fn root(&self) -> Result<Root, TlogError> {
+        self.subtree_root(&mut TileCache::new(), 0, to_usize(self.size)?)
+    }
+
+    /// `PATH(index, D[tree_size])`, the audit path of the leaf at `index`
+    pub fn inclusion_proof(&self, index: u64, tree_size: u64) -> Result<Vec<Hash>, TlogError> {
+        let (index, tree_size) = (to_usize(index)?, self.check_size(tree_size)?);
+        if index >= tree_size {
+            return Err(MerkleError::IndexOutOfRange {
+                index,
+                len: tree_size,
+            }
+            .into());
+        }
+
+        let mut cache = TileCache::new();
+        let mut proof = vec![];
+        let (mut index, mut start, mut n) = (index, 0, tree_size);
+        while n > 1 {
+            let k = largest_power_of_two_below(n);
+            if index < k {
+                proof.push(self.subtree_root(&mut cache, start + k, n - k)?);
+                n = k;
+            } else {
+                proof.push(self.subtree_root(&mut cache, start, k)?);
+                index -= k;
+                start += k;
+                n -= k;
+            }
+        }
+
+        // siblings were collected from the root down
+        proof.reverse();
+        Ok(proof)
+    }
+
+    /// `PROOF(old_size, D[new_size])`, see `merkle_consistency_proof`
+    pub fn consistency_proof(&self, old_size: u64, new_size: u64) -> Result<Vec<Hash>, TlogError> {
+        let (old_size, new_size) = (to_usize(old_size)?, self.check_size(new_size)?);
+        if old_size > new_size {
+            return Err(MerkleError::IndexOutOfRange {
+                index: old_size,
+                len: new_size,
+            }
+            .into());
+        }
+
+        let mut cache = TileCache::new();
+        let mut proof = vec![];
+        if old_size > 0 {
+            subproof(
+                old_size,
+                0,
+                new_size,
+                true,
+                &mut |start, n| self.subtree_root(&mut cache, start, n),
+                &mut proof,
+            )?;
+        }
+
+        Ok(proof)
+    }
+
+    fn check_size(&self, tree_size: u64) -> Result<usize, TlogError> {
+        let (tree_size, size) = (to_usize(tree_size)?, to_usize(self.size)?);
+        if tree_size > size {
+            return Err(MerkleError::IndexOutOfRange {
+                index: tree_size,
+                len: size,
+            }
+            .into());
+        }
+
+        Ok(tree_size)
+    }
+
+    /// `MTH(D[start:start + n])`, from the complete subtrees that make up the range
+    fn subtree_root(
+        &self,
+        cache: &mut TileCache,
+        mut start: usize,
+        n: usize,
+    ) -> Result<Hash, TlogError> {
+        let end = start + n;
+        let mut subtrees = vec![];
+        while start < end {
+            let mut height = start.trailing_zeros().min(usize::BITS - 1);
+            while start + (1 << height) > end {
+                height -= 1;
+            }
+
+            subtrees.push(self.node(cache, height, start >> height)?);
+            start += 1 << height;
+        }
+
+        Ok(fold_right(&subtrees))
+    }
+
+    /// The node `index` of tree level `height`, which must be a complete subtree
+    fn node(&self, cache: &mut TileCache, height: u32, index: usize) -> Result<Hash, TlogError> {
+        let level = height / TILE_HEIGHT;
+        let depth = height % TILE_HEIGHT;
+        let first = index << depth;
+
+        let tile = Tile::containing(level as u8, first, to_usize(self.size)?);
+        let hashes = match cache.entry(tile) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(read_hashes(&self.fetcher, &tile)?),
+        };
+
+        let offset = first % TILE_WIDTH;
+        Ok(fold(&hashes[offset..offset + (1 << depth)]))
+    }
+}
+
+fn read_hashes<F: TileFetcher>(fetcher: &F, tile: &Tile) -> Result<Vec<Hash>, TlogError> {
+    let data = fetcher.fetch_tile(tile)?;
+    let width = usize::from(tile.width);
+    if data.len() % 32 != 0 || data.len() < width * 32 || data.len() > TILE_WIDTH * 32 {
+        return Err(TlogError::MalformedTile);
+    }
+
+    Ok(data
+        .chunks_exact(32)
+        .take(width)
+        .map(|hash| hash.try_into().unwrap_or_default())
+        .collect())
+}
+
+/// Root of a complete subtree, from its `2^k` nodes at one level
+fn fold(hashes: &[Hash]) -> Hash {
+    let hasher = hasher();
+    let mut layer = hashes.to_vec();
+
+    while layer.len() > 1 {
+        layer = layer
+            .chunks_exact(2)
+            .map(|pair| {
+                let mut buffer = [0u8; 32];
+                hasher.hash_nodes(&pair[0], &pair[1], &mut buffer);
+                buffer
+            })
+            .collect();
+    }
+
+    layer[0]
+}
+
+/// Root of a tree made of complete subtrees, largest first, as RFC 6962 shapes it
+///
+/// The subtrees are bagged like MMR peaks, only the empty tree differs.
+fn fold_right(subtrees: &[Hash]) -> Root {
+    let hasher = hasher();
+
+    if subtrees.is_empty() {
+        return EmptyRoot::HashOfEmpty.root(&hasher).unwrap_or([0u8; 32]);
+    }
+
+    bag_peaks(subtrees, &hasher)
+}
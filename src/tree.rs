@@ -1,42 +1,367 @@
+use crate::error::MerkleError;
 use crate::hasher::{Hashable, Keccak256Hasher};
-use crate::merkle_proof::merkle_proof;
-use crate::merkle_proof_check::merkle_proof_check;
-use crate::merkle_root::merkle_root;
+use crate::merkle_consistency_check::merkle_consistency_check;
+use crate::merkle_consistency_proof::consistency_proof_from_layers;
+use crate::merkle_layers::{
+    merkle_layers_with_options, proof_from_layers, rebuild_layers_from, rehash_paths, sort_leaves,
+};
+use crate::merkle_multiproof::{multiproof_from_layers, MultiProof};
+use crate::merkle_multiproof_check::merkle_multiproof_check;
+use crate::merkle_proof::{check_index, find_leaf, leaf_indices};
+use crate::merkle_proof_check::{merkle_proof_check, merkle_proof_check_sorted};
 use crate::node::Node;
-use crate::{Leaf, Proof, Root};
+use crate::options::{EmptyRoot, OddNodeStrategy, RemoveMode, TreeOptions};
+use crate::utils::hash_leaves;
+use crate::{Hash, Leaf, Proof, Root, HASH_LEN};
 
 /// # 🌳 Merkle Tree
 /// - You can pass raw data
 /// - They will be hashed by the provided hash function (default: keccak-256)
-pub struct MerkleTree<H: Hashable = Keccak256Hasher> {
-    pub leaves: Vec<Leaf>,
-    pub root: Root,
-    pub hasher: H,
+/// - Every layer is built once and cached, so proofs are served in O(log n)
+pub struct MerkleTree<H: Hashable<N> = Keccak256Hasher, const N: usize = HASH_LEN> {
+    layers: Vec<Vec<Leaf<N>>>,
+    hasher: H,
+    options: TreeOptions<N>,
+    /// Only set with `options.sort_leaves`
+    sorted: Option<SortedLeaves<N>>,
+}
+
+/// The leaves as given, and where each one ended up after sorting
+struct SortedLeaves<const N: usize> {
+    original: Vec<Leaf<N>>,
+    positions: Vec<usize>,
 }
 
 impl MerkleTree<Keccak256Hasher> {
     /// Create a new Merkle Tree with the default Keccak256 hasher
-    pub fn new(leaves: Vec<Leaf>) -> Self {
+    pub fn new(leaves: Vec<Leaf>) -> Result<Self, MerkleError> {
         Self::new_with_hasher(leaves, Keccak256Hasher)
     }
 }
 
-impl<H: Hashable> MerkleTree<H> {
+impl<const N: usize, H: Hashable<N>> MerkleTree<H, N> {
     /// Create a new Merkle Tree with a custom hasher
-    pub fn new_with_hasher(leaves: Vec<Leaf>, hasher: H) -> Self {
-        let root = merkle_root(&leaves, &hasher);
-        MerkleTree {
-            leaves: leaves.clone(),
-            root,
+    pub fn new_with_hasher(leaves: Vec<Leaf<N>>, hasher: H) -> Result<Self, MerkleError> {
+        Self::new_with_options(leaves, hasher, TreeOptions::default())
+    }
+
+    /// Create a new Merkle Tree with a custom hasher and options
+    pub fn new_with_options(
+        leaves: Vec<Leaf<N>>,
+        hasher: H,
+        options: TreeOptions<N>,
+    ) -> Result<Self, MerkleError> {
+        if leaves.is_empty() {
+            options.empty_root.root(&hasher)?;
+        }
+
+        let mut tree = MerkleTree {
+            layers: vec![],
             hasher,
+            options,
+            sorted: None,
+        };
+        if tree.options.sort_leaves {
+            tree.rebuild_sorted(leaves);
+        } else {
+            tree.layers = merkle_layers_with_options(&leaves, &tree.hasher, &tree.options);
+        }
+
+        Ok(tree)
+    }
+
+    /// Create a new Merkle Tree from raw data, hashing each piece with `Hashable::hash_leaf`
+    pub fn new_from_data<D: AsRef<[u8]>>(
+        data: &[D],
+        hasher: H,
+        options: TreeOptions<N>,
+    ) -> Result<Self, MerkleError> {
+        Self::new_with_options(hash_leaves(data, &hasher), hasher, options)
+    }
+
+    /// Hash raw data into a leaf of this tree, e.g. before checking a proof of it
+    pub fn hash_leaf(&self, data: &[u8]) -> Leaf<N> {
+        let mut buffer = [0u8; N];
+        self.hasher.hash_leaf(data, &mut buffer);
+        buffer
+    }
+
+    /// The leaves of the tree, in insertion order or sorted with `options.sort_leaves`
+    pub fn leaves(&self) -> &[Leaf<N>] {
+        &self.layers[0]
+    }
+
+    /// The leaves in the order they were given, which index-based methods refer to
+    pub fn original_leaves(&self) -> &[Leaf<N>] {
+        match &self.sorted {
+            Some(sorted) => &sorted.original,
+            None => self.leaves(),
+        }
+    }
+
+    /// Position in `leaves()` of the leaf given at `index`
+    pub fn tree_index(&self, index: usize) -> Result<usize, MerkleError> {
+        check_index(self.original_leaves(), index)?;
+
+        match &self.sorted {
+            Some(sorted) => Ok(sorted.positions[index]),
+            None => Ok(index),
+        }
+    }
+
+    /// The Merkle root, an empty tree follows `options.empty_root`
+    pub fn root(&self) -> Root<N> {
+        match self.layers[self.layers.len() - 1].first() {
+            Some(root) => *root,
+            // a tree with `EmptyRoot::Error` is never empty
+            None => self
+                .options
+                .empty_root
+                .root(&self.hasher)
+                .unwrap_or([0u8; N]),
         }
     }
 
-    pub fn make_proof(&self, leaf: Leaf) -> Vec<Node> {
-        merkle_proof(&self.leaves, leaf, &self.hasher)
+    pub fn hasher(&self) -> &H {
+        &self.hasher
     }
 
-    pub fn check_proof(&self, proof: Proof, leaf: Leaf) -> Leaf {
+    pub fn options(&self) -> &TreeOptions<N> {
+        &self.options
+    }
+
+    /// Every cached layer, from the leaves up to the root
+    pub fn layers(&self) -> &[Vec<Leaf<N>>] {
+        &self.layers
+    }
+
+    /// Append a leaf, rehashing only the right-most path, and return the new root
+    pub fn push(&mut self, leaf: Leaf<N>) -> Root<N> {
+        self.extend(std::iter::once(leaf))
+    }
+
+    /// Append many leaves at once and return the new root
+    ///
+    /// Every touched node is hashed once, so `k` leaves cost O(k + log n).
+    /// A tree with `options.sort_leaves` is rebuilt instead.
+    pub fn extend<I: IntoIterator<Item = Leaf<N>>>(&mut self, leaves: I) -> Root<N> {
+        if let Some(sorted) = self.sorted.take() {
+            let mut original = sorted.original;
+            original.extend(leaves);
+            self.rebuild_sorted(original);
+
+            return self.root();
+        }
+
+        let start = self.leaves().len();
+        self.layers[0].extend(leaves);
+
+        if self.leaves().len() > start {
+            rebuild_layers_from(&mut self.layers, start, &self.hasher, self.options.odd_node);
+        }
+
+        self.root()
+    }
+
+    /// Replace the leaf at `index`, rehashing only its ancestors, and return the new root
+    pub fn set_leaf(&mut self, index: usize, leaf: Leaf<N>) -> Result<Root<N>, MerkleError> {
+        self.set_leaves([(index, leaf)])
+    }
+
+    /// Replace many leaves at once and return the new root
+    ///
+    /// Ancestors shared by several updated leaves are rehashed only once.
+    /// When an index appears twice, the last update wins.
+    /// Nothing is changed if any index is out of range.
+    /// A tree with `options.sort_leaves` is rebuilt instead.
+    pub fn set_leaves<I: IntoIterator<Item = (usize, Leaf<N>)>>(
+        &mut self,
+        updates: I,
+    ) -> Result<Root<N>, MerkleError> {
+        let updates: Vec<(usize, Leaf<N>)> = updates.into_iter().collect();
+        for (index, _) in &updates {
+            check_index(self.original_leaves(), *index)?;
+        }
+
+        if let Some(sorted) = self.sorted.take() {
+            let mut original = sorted.original;
+            for (index, leaf) in updates {
+                original[index] = leaf;
+            }
+            self.rebuild_sorted(original);
+
+            return Ok(self.root());
+        }
+
+        let mut indices = Vec::with_capacity(updates.len());
+        for (index, leaf) in updates {
+            self.layers[0][index] = leaf;
+            indices.push(index);
+        }
+        indices.sort_unstable();
+        indices.dedup();
+
+        rehash_paths(
+            &mut self.layers,
+            &indices,
+            &self.hasher,
+            self.options.odd_node,
+        );
+
+        Ok(self.root())
+    }
+
+    /// Remove the leaf at `index` following `options.remove_mode` and return the new root
+    ///
+    /// `RemoveMode::Compact` rebuilds only the nodes right of the leaf,
+    /// `RemoveMode::Tombstone` rehashes only its ancestors.
+    /// A tree with `options.sort_leaves` is rebuilt instead.
+    pub fn remove(&mut self, index: usize) -> Result<Root<N>, MerkleError> {
+        check_index(self.original_leaves(), index)?;
+
+        match self.options.remove_mode {
+            RemoveMode::Tombstone(tombstone) => self.set_leaf(index, tombstone),
+            RemoveMode::Compact => {
+                if self.original_leaves().len() == 1 && self.options.empty_root == EmptyRoot::Error
+                {
+                    return Err(MerkleError::EmptyTree);
+                }
+
+                if let Some(sorted) = self.sorted.take() {
+                    let mut original = sorted.original;
+                    original.remove(index);
+                    self.rebuild_sorted(original);
+
+                    return Ok(self.root());
+                }
+
+                self.layers[0].remove(index);
+                rebuild_layers_from(&mut self.layers, index, &self.hasher, self.options.odd_node);
+
+                Ok(self.root())
+            }
+        }
+    }
+
+    /// Prove the first occurrence of `leaf`, use `make_proof_at` for the others
+    pub fn make_proof(&self, leaf: Leaf<N>) -> Result<Vec<Node<N>>, MerkleError> {
+        let index = find_leaf(self.leaves(), leaf)?;
+
+        Ok(proof_from_layers(
+            &self.layers,
+            index,
+            &self.hasher,
+            self.options.odd_node,
+        ))
+    }
+
+    /// Prove the leaf at `index`, so any copy of a duplicated leaf can be proven
+    pub fn make_proof_at(&self, index: usize) -> Result<Vec<Node<N>>, MerkleError> {
+        let index = self.tree_index(index)?;
+
+        Ok(proof_from_layers(
+            &self.layers,
+            index,
+            &self.hasher,
+            self.options.odd_node,
+        ))
+    }
+
+    /// Every index that holds `leaf`, in ascending order
+    pub fn leaf_indices(&self, leaf: Leaf<N>) -> Vec<usize> {
+        leaf_indices(self.original_leaves(), leaf)
+    }
+
+    pub fn check_proof(&self, proof: Proof<N>, leaf: Leaf<N>) -> Result<Root<N>, MerkleError> {
         merkle_proof_check(proof, leaf, &self.hasher)
     }
+
+    /// Check a proof without sides, see `SortedPairHasher`
+    pub fn check_sorted_proof(
+        &self,
+        proof: &[Hash<N>],
+        leaf: Leaf<N>,
+    ) -> Result<Root<N>, MerkleError> {
+        merkle_proof_check_sorted(proof, leaf, &self.hasher)
+    }
+
+    /// Prove the leaves at `indices` at once, see `MultiProof`
+    pub fn make_multiproof(&self, indices: &[usize]) -> Result<MultiProof<N>, MerkleError> {
+        let indices = indices
+            .iter()
+            .map(|&index| self.tree_index(index))
+            .collect::<Result<Vec<usize>, MerkleError>>()?;
+
+        multiproof_from_layers(&self.layers, &indices, &self.hasher, self.options.odd_node)
+    }
+
+    pub fn check_multiproof(&self, multiproof: &MultiProof<N>) -> Result<Root<N>, MerkleError> {
+        merkle_multiproof_check(
+            &multiproof.proof,
+            &multiproof.proof_flags,
+            &multiproof.leaves,
+            &self.hasher,
+        )
+    }
+
+    /// Prove that the tree of the first `old_size` leaves is a prefix of this one
+    ///
+    /// See `merkle_consistency_proof`, the tree must promote unpaired nodes.
+    pub fn make_consistency_proof(&self, old_size: usize) -> Result<Vec<Hash<N>>, MerkleError> {
+        if self.options.odd_node != OddNodeStrategy::Promote {
+            return Err(MerkleError::UnsupportedOddNodeStrategy);
+        }
+
+        consistency_proof_from_layers(&self.layers, old_size)
+    }
+
+    /// Check that the tree of `old_size` leaves with `old_root` is a prefix of this one
+    pub fn check_consistency_proof(
+        &self,
+        proof: &[Hash<N>],
+        old_size: usize,
+        old_root: Root<N>,
+    ) -> Result<(), MerkleError> {
+        merkle_consistency_check(
+            proof,
+            old_size,
+            self.leaves().len(),
+            old_root,
+            self.root(),
+            &self.hasher,
+        )
+    }
+
+    /// Sort `original` and rebuild every layer from it
+    fn rebuild_sorted(&mut self, original: Vec<Leaf<N>>) {
+        let (leaves, positions) = sort_leaves(&original, self.options.dedup_leaves);
+
+        self.layers = merkle_layers_with_options(&leaves, &self.hasher, &self.options);
+        self.sorted = Some(SortedLeaves {
+            original,
+            positions,
+        });
+    }
+}
+
+impl<const N: usize, H: Hashable<N> + Default> FromIterator<Leaf<N>> for MerkleTree<H, N> {
+    fn from_iter<I: IntoIterator<Item = Leaf<N>>>(leaves: I) -> Self {
+        let hasher = H::default();
+        let leaves: Vec<Leaf<N>> = leaves.into_iter().collect();
+
+        let options = TreeOptions::default();
+
+        MerkleTree {
+            layers: merkle_layers_with_options(&leaves, &hasher, &options),
+            hasher,
+            options,
+            sorted: None,
+        }
+    }
+}
+
+impl<const N: usize, H: Hashable<N>> Extend<Leaf<N>> for MerkleTree<H, N> {
+    fn extend<I: IntoIterator<Item = Leaf<N>>>(&mut self, leaves: I) {
+        MerkleTree::extend(self, leaves);
+    }
 }
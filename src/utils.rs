@@ -1,5 +1,5 @@
 use crate::hasher::{Hashable, Keccak256Hasher};
-use crate::Hash;
+use crate::{Hash, Leaf};
 
 /// Helper function to hash data using Keccak256 (for backward compatibility)
 pub fn hash_it(data: &[u8], buffer: &mut Hash) {
@@ -7,9 +7,20 @@ pub fn hash_it(data: &[u8], buffer: &mut Hash) {
     hasher.hash(data, buffer);
 }
 
-pub fn is_power_of_two(number: u32) -> bool {
-    let left: bool = number & (number - 1) == 0;
-    let right: bool = number != 0;
+/// Hash every piece of data into a leaf with `Hashable::hash_leaf`
+pub fn hash_leaves<const N: usize, H: Hashable<N>, D: AsRef<[u8]>>(
+    data: &[D],
+    hasher: &H,
+) -> Vec<Leaf<N>> {
+    data.iter()
+        .map(|data| {
+            let mut buffer = [0u8; N];
+            hasher.hash_leaf(data.as_ref(), &mut buffer);
+            buffer
+        })
+        .collect()
+}
 
-    left && right
+pub fn is_power_of_two(number: u32) -> bool {
+    number != 0 && number & (number - 1) == 0
 }
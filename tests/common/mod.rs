@@ -0,0 +1,29 @@
+//! Helpers shared by the integration tests
+
+// every test file only uses some of them
+#![allow(dead_code)]
+
+use merkletreers::utils::hash_it;
+use merkletreers::Leaf;
+
+/// The Keccak-256 hashes of "0", "1", ... as leaves
+pub fn make_leaves(count: usize) -> Vec<Leaf> {
+    (0..count)
+        .map(|i| {
+            let mut buffer = [0u8; 32];
+            hash_it(i.to_string().as_bytes(), &mut buffer);
+            buffer
+        })
+        .collect()
+}
+
+/// The Keccak-256 hash of every item of `data` as leaves
+pub fn hash_data(data: &[&str]) -> Vec<Leaf> {
+    data.iter()
+        .map(|d| {
+            let mut buffer = [0u8; 32];
+            hash_it(d.as_bytes(), &mut buffer);
+            buffer
+        })
+        .collect()
+}
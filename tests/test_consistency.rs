@@ -0,0 +1,179 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::hasher::Keccak256Hasher;
+use merkletreers::merkle_consistency_check::merkle_consistency_check;
+use merkletreers::merkle_consistency_proof::merkle_consistency_proof;
+use merkletreers::merkle_root::merkle_root;
+use merkletreers::tree::MerkleTree;
+use merkletreers::{MerkleError, OddNodeStrategy, TreeOptions};
+
+#[cfg(feature = "sha2")]
+fn hash(value: &str) -> merkletreers::Hash {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Consistency proofs published with RFC 6962 (see `test_rfc6962.rs` for the leaves)
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_rfc6962_vectors() {
+        use merkletreers::rfc6962::{hasher, leaf_hash, tree_from_data};
+
+        let data: Vec<Vec<u8>> = [
+            "",
+            "00",
+            "10",
+            "2021",
+            "3031",
+            "40414243",
+            "5051525354555657",
+            "606162636465666768696a6b6c6d6e6f",
+        ]
+        .iter()
+        .map(|leaf| hex::decode(leaf).unwrap())
+        .collect();
+        let leaves: Vec<_> = data.iter().map(|item| leaf_hash(item)).collect();
+
+        let vectors = [
+            (1, 1, vec![]),
+            (
+                1,
+                8,
+                vec![
+                    hash("96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"),
+                    hash("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                    hash("6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"),
+                ],
+            ),
+            (
+                6,
+                8,
+                vec![
+                    hash("0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a"),
+                    hash("ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0"),
+                    hash("d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7"),
+                ],
+            ),
+            (
+                2,
+                5,
+                vec![
+                    hash("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                    hash("bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"),
+                ],
+            ),
+        ];
+
+        for (old_size, new_size, proof) in vectors {
+            let old_tree = tree_from_data(&data[..old_size]);
+            let new_tree = tree_from_data(&data[..new_size]);
+
+            assert_eq!(
+                merkle_consistency_proof(&leaves[..new_size], old_size, &hasher()).unwrap(),
+                proof
+            );
+            assert_eq!(new_tree.make_consistency_proof(old_size).unwrap(), proof);
+            assert_eq!(
+                new_tree.check_consistency_proof(&proof, old_size, old_tree.root()),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_pair_of_sizes() {
+        let leaves = make_leaves(33);
+
+        for new_size in 0..=leaves.len() {
+            let new_root = merkle_root(&leaves[..new_size], &Keccak256Hasher).unwrap();
+
+            for old_size in 0..=new_size {
+                let old_root = merkle_root(&leaves[..old_size], &Keccak256Hasher).unwrap();
+                let proof =
+                    merkle_consistency_proof(&leaves[..new_size], old_size, &Keccak256Hasher)
+                        .unwrap();
+
+                assert_eq!(
+                    merkle_consistency_check(
+                        &proof,
+                        old_size,
+                        new_size,
+                        old_root,
+                        new_root,
+                        &Keccak256Hasher
+                    ),
+                    Ok(())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_mismatching_roots() {
+        let leaves = make_leaves(11);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let old_root = merkle_root(&leaves[..6], &Keccak256Hasher).unwrap();
+        let proof = tree.make_consistency_proof(6).unwrap();
+
+        assert_eq!(
+            tree.check_consistency_proof(&proof, 6, leaves[0]),
+            Err(MerkleError::OldRootMismatch)
+        );
+        assert_eq!(
+            merkle_consistency_check(&proof, 6, 11, old_root, leaves[0], &Keccak256Hasher),
+            Err(MerkleError::NewRootMismatch)
+        );
+
+        // a rewritten history gives another old root
+        let mut rewritten = leaves.clone();
+        rewritten[2] = leaves[3];
+        let rewritten_root = merkle_root(&rewritten[..6], &Keccak256Hasher).unwrap();
+        assert_eq!(
+            tree.check_consistency_proof(&proof, 6, rewritten_root),
+            Err(MerkleError::OldRootMismatch)
+        );
+    }
+
+    #[test]
+    fn test_malformed_proofs() {
+        let leaves = make_leaves(11);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let old_root = merkle_root(&leaves[..6], &Keccak256Hasher).unwrap();
+        let proof = tree.make_consistency_proof(6).unwrap();
+
+        assert_eq!(
+            tree.check_consistency_proof(&proof[..proof.len() - 1], 6, old_root),
+            Err(MerkleError::MalformedProof)
+        );
+        assert_eq!(
+            tree.check_consistency_proof(&[proof.clone(), proof.clone()].concat(), 6, old_root),
+            Err(MerkleError::MalformedProof)
+        );
+        assert_eq!(
+            tree.check_consistency_proof(&proof, 12, old_root),
+            Err(MerkleError::IndexOutOfRange { index: 12, len: 11 })
+        );
+        assert_eq!(
+            tree.make_consistency_proof(12),
+            Err(MerkleError::IndexOutOfRange { index: 12, len: 11 })
+        );
+    }
+
+    #[test]
+    fn test_needs_promoted_odd_nodes() {
+        let options = TreeOptions {
+            odd_node: OddNodeStrategy::Duplicate,
+            ..Default::default()
+        };
+        let tree = MerkleTree::new_with_options(make_leaves(5), Keccak256Hasher, options).unwrap();
+
+        assert_eq!(
+            tree.make_consistency_proof(3),
+            Err(MerkleError::UnsupportedOddNodeStrategy)
+        );
+    }
+}
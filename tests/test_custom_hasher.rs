@@ -33,18 +33,18 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let tree = MerkleTree::new(leaves.clone());
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
 
         // Verify root was generated
-        assert_ne!(tree.root, [0u8; 32]);
+        assert_ne!(tree.root(), [0u8; 32]);
 
         // Verify we can make proof
-        let proof = tree.make_proof(leaves[0]);
+        let proof = tree.make_proof(leaves[0]).unwrap();
         assert!(!proof.is_empty());
 
         // Verify proof
-        let computed_root = tree.check_proof(proof, leaves[0]);
-        assert_eq!(computed_root, tree.root);
+        let computed_root = tree.check_proof(proof, leaves[0]).unwrap();
+        assert_eq!(computed_root, tree.root());
     }
 
     #[test]
@@ -60,18 +60,18 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let tree = MerkleTree::new_with_hasher(leaves.clone(), hasher);
+        let tree = MerkleTree::new_with_hasher(leaves.clone(), hasher).unwrap();
 
         // Verify root was generated
-        assert_ne!(tree.root, [0u8; 32]);
+        assert_ne!(tree.root(), [0u8; 32]);
 
         // Verify we can make proof
-        let proof = tree.make_proof(leaves[0]);
+        let proof = tree.make_proof(leaves[0]).unwrap();
         assert!(!proof.is_empty());
 
         // Verify proof
-        let computed_root = tree.check_proof(proof, leaves[0]);
-        assert_eq!(computed_root, tree.root);
+        let computed_root = tree.check_proof(proof, leaves[0]).unwrap();
+        assert_eq!(computed_root, tree.root());
     }
 
     #[test]
@@ -100,11 +100,11 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let keccak_tree = MerkleTree::new_with_hasher(keccak_leaves, keccak_hasher);
-        let xor_tree = MerkleTree::new_with_hasher(xor_leaves, xor_hasher);
+        let keccak_tree = MerkleTree::new_with_hasher(keccak_leaves, keccak_hasher).unwrap();
+        let xor_tree = MerkleTree::new_with_hasher(xor_leaves, xor_hasher).unwrap();
 
         // Different hashers should produce different roots
-        assert_ne!(keccak_tree.root, xor_tree.root);
+        assert_ne!(keccak_tree.root(), xor_tree.root());
     }
 
     #[test]
@@ -119,14 +119,15 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let tree = MerkleTree::new_with_hasher(leaves.clone(), hasher);
+        let tree = MerkleTree::new_with_hasher(leaves.clone(), hasher).unwrap();
 
         // Test proof for each leaf
         for leaf in &leaves {
-            let proof = tree.make_proof(*leaf);
-            let computed_root = tree.check_proof(proof, *leaf);
+            let proof = tree.make_proof(*leaf).unwrap();
+            let computed_root = tree.check_proof(proof, *leaf).unwrap();
             assert_eq!(
-                computed_root, tree.root,
+                computed_root,
+                tree.root(),
                 "Proof verification failed for a leaf"
             );
         }
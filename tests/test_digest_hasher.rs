@@ -0,0 +1,104 @@
+#![cfg(feature = "digest")]
+
+use digest::consts::U32;
+use digest::{FixedOutput, HashMarker, Output, OutputSizeUser, Update};
+use merkletreers::hasher::Hashable;
+use merkletreers::tree::MerkleTree;
+use merkletreers::utils::hash_leaves;
+use merkletreers::{DigestHasher, Leaf};
+
+/// A digest only known through the RustCrypto traits, like an in-house hash
+/// WARNING: This is NOT cryptographically secure! Use only for testing.
+#[derive(Clone, Default)]
+struct XorDigest {
+    state: [u8; 32],
+    len: usize,
+}
+
+impl HashMarker for XorDigest {}
+
+impl OutputSizeUser for XorDigest {
+    type OutputSize = U32;
+}
+
+impl Update for XorDigest {
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.state[self.len % 32] ^= byte;
+            self.len += 1;
+        }
+    }
+}
+
+impl FixedOutput for XorDigest {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.state);
+    }
+}
+
+/// The same hash, written by hand against `Hashable`
+#[derive(Clone, Copy, Debug)]
+struct XorHasher;
+
+impl Hashable for XorHasher {
+    fn hash(&self, data: &[u8], buffer: &mut [u8; 32]) {
+        buffer.fill(0);
+        for (i, byte) in data.iter().enumerate() {
+            buffer[i % 32] ^= byte;
+        }
+    }
+}
+
+fn make_leaves<H: Hashable>(hasher: &H) -> Vec<Leaf> {
+    hash_leaves(&["a", "b", "c", "d", "e"], hasher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_digest_plugs_into_the_tree() {
+        let hasher = DigestHasher::<XorDigest>::new();
+        let leaves = make_leaves(&hasher);
+        assert_eq!(leaves, make_leaves(&XorHasher));
+
+        let tree = MerkleTree::new_with_hasher(leaves.clone(), hasher).unwrap();
+        let expected = MerkleTree::new_with_hasher(leaves, XorHasher).unwrap();
+        assert_eq!(tree.root(), expected.root());
+        assert_eq!(
+            tree.make_proof_at(3).unwrap(),
+            expected.make_proof_at(3).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_debug_names_the_digest() {
+        let hasher = DigestHasher::<XorDigest>::default();
+        assert!(format!("{hasher:?}").contains("XorDigest"));
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_matches_sha256_hasher() {
+        use merkletreers::Sha256Hasher;
+
+        let hasher = DigestHasher::<sha2::Sha256>::new();
+        let tree = MerkleTree::new_with_hasher(make_leaves(&hasher), hasher).unwrap();
+        let expected =
+            MerkleTree::new_with_hasher(make_leaves(&Sha256Hasher), Sha256Hasher).unwrap();
+        assert_eq!(tree.root(), expected.root());
+    }
+
+    #[cfg(feature = "sha3")]
+    #[test]
+    fn test_matches_keccak256_hasher() {
+        use merkletreers::Keccak256Hasher;
+
+        let hasher = DigestHasher::<sha3::Keccak256>::new();
+        let tree = MerkleTree::new_with_hasher(make_leaves(&hasher), hasher).unwrap();
+        let expected =
+            MerkleTree::new_with_hasher(make_leaves(&Keccak256Hasher), Keccak256Hasher).unwrap();
+        assert_eq!(tree.root(), expected.root());
+    }
+}
@@ -0,0 +1,111 @@
+use merkletreers::hasher::{DomainSeparatedHasher, Hashable, Keccak256Hasher};
+use merkletreers::merkle_proof::merkle_proof_at;
+use merkletreers::merkle_proof_check::merkle_proof_check;
+use merkletreers::merkle_root::merkle_root;
+use merkletreers::node::{Node, Side};
+use merkletreers::tree::MerkleTree;
+use merkletreers::utils::{hash_it, hash_leaves};
+use merkletreers::{Leaf, TreeOptions};
+
+fn make_data(count: usize) -> Vec<String> {
+    (0..count).map(|i| i.to_string()).collect()
+}
+
+fn keccak(data: &[u8]) -> Leaf {
+    let mut buffer = [0u8; 32];
+    hash_it(data, &mut buffer);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leaves_and_nodes_are_prefixed() {
+        let hasher = DomainSeparatedHasher::new(Keccak256Hasher);
+        let leaves = hash_leaves(&["a", "b"], &hasher);
+
+        assert_eq!(leaves[0], keccak(b"\x00a"));
+        assert_eq!(leaves[1], keccak(b"\x00b"));
+
+        let mut node = vec![0x01];
+        node.extend_from_slice(&leaves[0]);
+        node.extend_from_slice(&leaves[1]);
+        assert_eq!(merkle_root(&leaves, &hasher).unwrap(), keccak(&node));
+    }
+
+    #[test]
+    fn test_configurable_prefixes() {
+        let hasher = DomainSeparatedHasher::with_prefixes(Keccak256Hasher, 0xaa, 0xbb);
+        let leaves = hash_leaves(&["a", "b"], &hasher);
+
+        assert_eq!(leaves[0], keccak(b"\xaaa"));
+
+        let mut node = vec![0xbb];
+        node.extend_from_slice(&leaves[0]);
+        node.extend_from_slice(&leaves[1]);
+        assert_eq!(merkle_root(&leaves, &hasher).unwrap(), keccak(&node));
+    }
+
+    #[test]
+    fn test_internal_node_cannot_pass_as_leaf() {
+        let data = make_data(4);
+
+        // without domain separation, the left child of the root verifies as a leaf
+        let leaves = hash_leaves(&data, &Keccak256Hasher);
+        let tree = MerkleTree::new(leaves).unwrap();
+        let node = tree.layers()[1][0];
+        let forged = vec![Node {
+            data: tree.layers()[1][1],
+            side: Side::RIGHT,
+        }];
+        assert_eq!(
+            merkle_proof_check(forged, node, &Keccak256Hasher).unwrap(),
+            tree.root()
+        );
+
+        // with it, the same forgery gives another root
+        let hasher = DomainSeparatedHasher::new(Keccak256Hasher);
+        let tree = MerkleTree::new_from_data(&data, hasher, TreeOptions::default()).unwrap();
+        let mut preimage = tree.layers()[0][0].to_vec();
+        preimage.extend_from_slice(&tree.layers()[0][1]);
+        let forged = vec![Node {
+            data: tree.layers()[1][1],
+            side: Side::RIGHT,
+        }];
+        assert_ne!(
+            tree.check_proof(forged, tree.hash_leaf(&preimage)).unwrap(),
+            tree.root()
+        );
+    }
+
+    #[test]
+    fn test_proofs_of_raw_data() {
+        let hasher = DomainSeparatedHasher::new(Keccak256Hasher);
+        let data = make_data(13);
+        let tree = MerkleTree::new_from_data(&data, hasher, TreeOptions::default()).unwrap();
+
+        for (index, item) in data.iter().enumerate() {
+            let leaf = tree.hash_leaf(item.as_bytes());
+            let proof = tree.make_proof_at(index).unwrap();
+
+            assert_eq!(
+                proof,
+                merkle_proof_at(tree.leaves(), index, &hasher).unwrap()
+            );
+            assert_eq!(
+                merkle_proof_check(proof, leaf, &hasher).unwrap(),
+                tree.root()
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_hashers_hash_leaves_unprefixed() {
+        let mut buffer = [0u8; 32];
+        Keccak256Hasher.hash_leaf(b"a", &mut buffer);
+
+        assert_eq!(buffer, keccak(b"a"));
+    }
+}
@@ -0,0 +1,83 @@
+use merkletreers::hasher::Keccak256Hasher;
+use merkletreers::merkle_proof::merkle_proof_at;
+use merkletreers::merkle_root::{merkle_root, merkle_root_with_options};
+use merkletreers::tree::MerkleTree;
+use merkletreers::{EmptyRoot, MerkleError, Root, TreeOptions};
+
+// keccak-256 of the empty string
+const KECCAK_EMPTY: Root = [
+    197, 210, 70, 1, 134, 247, 35, 60, 146, 126, 125, 178, 220, 199, 3, 192, 229, 0, 182, 83, 202,
+    130, 39, 59, 123, 250, 216, 4, 93, 133, 164, 112,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(empty_root: EmptyRoot) -> TreeOptions {
+        TreeOptions {
+            empty_root,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_empty_root_is_zero() {
+        assert_eq!(merkle_root(&[], &Keccak256Hasher), Ok([0u8; 32]));
+        assert_eq!(MerkleTree::new(vec![]).unwrap().root(), [0u8; 32]);
+    }
+
+    #[test]
+    fn test_hash_of_empty_root() {
+        let options = options(EmptyRoot::HashOfEmpty);
+
+        assert_eq!(
+            merkle_root_with_options(&[], &Keccak256Hasher, &options),
+            Ok(KECCAK_EMPTY)
+        );
+
+        let tree = MerkleTree::new_with_options(vec![], Keccak256Hasher, options).unwrap();
+        assert_eq!(tree.root(), KECCAK_EMPTY);
+    }
+
+    #[test]
+    fn test_error_empty_root() {
+        let options = options(EmptyRoot::Error);
+
+        assert_eq!(
+            merkle_root_with_options(&[], &Keccak256Hasher, &options),
+            Err(MerkleError::EmptyTree)
+        );
+        assert!(matches!(
+            MerkleTree::new_with_options(vec![], Keccak256Hasher, options),
+            Err(MerkleError::EmptyTree)
+        ));
+    }
+
+    #[test]
+    fn test_policy_does_not_change_non_empty_roots() {
+        let leaves = vec![[1u8; 32], [2u8; 32], [3u8; 32]];
+        let expected = merkle_root(&leaves, &Keccak256Hasher).unwrap();
+
+        for policy in [EmptyRoot::Zero, EmptyRoot::HashOfEmpty, EmptyRoot::Error] {
+            let tree =
+                MerkleTree::new_with_options(leaves.clone(), Keccak256Hasher, options(policy))
+                    .unwrap();
+            assert_eq!(tree.root(), expected);
+        }
+    }
+
+    #[test]
+    fn test_empty_tree_has_no_proofs() {
+        let tree =
+            MerkleTree::new_with_options(vec![], Keccak256Hasher, options(EmptyRoot::HashOfEmpty))
+                .unwrap();
+
+        assert_eq!(tree.make_proof_at(0), Err(MerkleError::EmptyTree));
+        assert_eq!(tree.make_proof([0u8; 32]), Err(MerkleError::EmptyTree));
+        assert_eq!(
+            merkle_proof_at(&[], 0, &Keccak256Hasher),
+            Err(MerkleError::EmptyTree)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::hash_data;
+use merkletreers::hasher::Keccak256Hasher;
+use merkletreers::merkle_proof::{merkle_proof, merkle_proof_at};
+use merkletreers::merkle_proof_check::merkle_proof_check;
+use merkletreers::merkle_proof_mixed::merkle_proof_mixed_tree;
+use merkletreers::node::{Node, Side};
+use merkletreers::tree::MerkleTree;
+use merkletreers::utils::is_power_of_two;
+use merkletreers::MerkleError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_leaf() {
+        let leaves = hash_data(&["a", "b", "c"]);
+        let missing = hash_data(&["z"])[0];
+
+        assert_eq!(
+            merkle_proof(&leaves, missing, &Keccak256Hasher),
+            Err(MerkleError::LeafNotFound)
+        );
+        assert_eq!(
+            merkle_proof_mixed_tree(&leaves, missing, &Keccak256Hasher),
+            Err(MerkleError::LeafNotFound)
+        );
+
+        let tree = MerkleTree::new(leaves).unwrap();
+        assert_eq!(tree.make_proof(missing), Err(MerkleError::LeafNotFound));
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let leaf = hash_data(&["a"])[0];
+
+        assert!(!is_power_of_two(0));
+        assert_eq!(
+            merkle_proof(&[], leaf, &Keccak256Hasher),
+            Err(MerkleError::EmptyTree)
+        );
+        assert_eq!(
+            merkle_proof_at(&[], 0, &Keccak256Hasher),
+            Err(MerkleError::EmptyTree)
+        );
+
+        let tree = MerkleTree::new(vec![]).unwrap();
+        assert_eq!(tree.make_proof_at(0), Err(MerkleError::EmptyTree));
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let leaves = hash_data(&["a", "b", "c"]);
+
+        assert_eq!(
+            merkle_proof_at(&leaves, 3, &Keccak256Hasher),
+            Err(MerkleError::IndexOutOfRange { index: 3, len: 3 })
+        );
+    }
+
+    #[test]
+    fn test_side_conversion() {
+        assert_eq!(Side::try_from(0), Ok(Side::LEFT));
+        assert_eq!(Side::try_from(1), Ok(Side::RIGHT));
+        assert_eq!(Side::try_from(2), Err(MerkleError::InvalidSide(2)));
+        assert_eq!(u8::from(Side::RIGHT), 1);
+    }
+
+    #[test]
+    fn test_malformed_proof() {
+        let leaf = hash_data(&["a"])[0];
+        let proof = vec![
+            Node {
+                data: leaf,
+                side: Side::LEFT,
+            };
+            usize::BITS as usize + 1
+        ];
+
+        assert_eq!(
+            merkle_proof_check(proof, leaf, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+}
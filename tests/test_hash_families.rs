@@ -0,0 +1,124 @@
+//! Published test vectors for the feature-gated hashers, and roots shared with
+//! other Merkle libraries (merkletreejs promotes unpaired nodes the same way)
+
+// every test needs its feature, the helpers may go unused
+#![allow(dead_code, unused_imports)]
+
+use merkletreers::hasher::Hashable;
+use merkletreers::tree::MerkleTree;
+use merkletreers::utils::hash_leaves;
+use merkletreers::Hash;
+
+fn digest<H: Hashable>(hasher: &H, data: &[u8]) -> String {
+    let mut buffer = [0u8; 32];
+    hasher.hash(data, &mut buffer);
+    hex::encode(buffer)
+}
+
+fn root_of_abc<H: Hashable>(hasher: H) -> String {
+    let leaves = hash_leaves(&["a", "b", "c"], &hasher);
+    hex::encode(MerkleTree::new_with_hasher(leaves, hasher).unwrap().root())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha256() {
+        use merkletreers::Sha256Hasher;
+
+        // FIPS 180-2, appendix B
+        assert_eq!(
+            digest(&Sha256Hasher, b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            digest(&Sha256Hasher, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            digest(
+                &Sha256Hasher,
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            ),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        // the SHA-256 example of the merkletreejs README
+        assert_eq!(
+            root_of_abc(Sha256Hasher),
+            "7075152d03a5cd92104887b476862778ec0c87be5c2fa1c0a90f87c49fad6eff"
+        );
+    }
+
+    #[cfg(feature = "sha3")]
+    #[test]
+    fn test_sha3_256() {
+        use merkletreers::{Keccak256Hasher, Sha3_256Hasher};
+
+        // FIPS 202 examples
+        assert_eq!(
+            digest(&Sha3_256Hasher, b""),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            digest(&Sha3_256Hasher, b"abc"),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_ne!(
+            digest(&Sha3_256Hasher, b"abc"),
+            digest(&Keccak256Hasher, b"abc")
+        );
+
+        assert_eq!(
+            root_of_abc(Sha3_256Hasher),
+            "b940dc53d707e4d9dfe9300664c6bbc4ab0c9f045d74441bfeda030cedbdbcba"
+        );
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn test_blake2b_256() {
+        use merkletreers::Blake2b256Hasher;
+
+        assert_eq!(
+            digest(&Blake2b256Hasher, b""),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+        assert_eq!(
+            digest(&Blake2b256Hasher, b"abc"),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+
+        assert_eq!(
+            root_of_abc(Blake2b256Hasher),
+            "350bf288b7179755b0d6f6e91f8e6fa5b2ac2d8bcf6d0b3882b81a2d3171bc8c"
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_blake3() {
+        use merkletreers::Blake3Hasher;
+
+        // test_vectors.json of the BLAKE3 reference implementation
+        assert_eq!(
+            digest(&Blake3Hasher, b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            digest(&Blake3Hasher, b"abc"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+
+        let hash = |data: &[u8]| -> Hash { *blake3::hash(data).as_bytes() };
+        let (a, b, c) = (hash(b"a"), hash(b"b"), hash(b"c"));
+        let ab = hash(&[a, b].concat());
+        assert_eq!(
+            root_of_abc(Blake3Hasher),
+            hex::encode(hash(&[ab, c].concat()))
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use merkletreers::hasher::{Hashable, Keccak256Hasher};
+use merkletreers::merkle_consistency_check::merkle_consistency_check;
+use merkletreers::merkle_multiproof_check::merkle_multiproof_check;
+use merkletreers::merkle_proof::merkle_proof_at;
+use merkletreers::merkle_proof_check::merkle_proof_check;
+use merkletreers::merkle_root::merkle_root;
+use merkletreers::node::{Node, Side};
+use merkletreers::tree::MerkleTree;
+use merkletreers::utils::{hash_it, hash_leaves};
+use merkletreers::{Leaf, RemoveMode, SortedPairHasher, TreeOptions, TruncatedHasher};
+
+/// Keccak-256 hashes cut to their first 20 bytes
+type Keccak160 = TruncatedHasher;
+
+fn make_leaves(count: usize) -> Vec<Leaf<20>> {
+    let data: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+    hash_leaves(&data, &Keccak160::default())
+}
+
+fn keccak160(data: &[u8]) -> Leaf<20> {
+    let mut buffer = [0u8; 32];
+    hash_it(data, &mut buffer);
+    buffer[..20].try_into().unwrap()
+}
+
+/// The node of two 20-byte children, zero-extended to 32 bytes each
+fn keccak160_pair(left: &Leaf<20>, right: &Leaf<20>) -> Leaf<20> {
+    let mut data = [0u8; 64];
+    data[..20].copy_from_slice(left);
+    data[32..52].copy_from_slice(right);
+    keccak160(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncated_hashes() {
+        let leaves = make_leaves(3);
+        assert_eq!(leaves[0], keccak160(b"0"));
+
+        let left = keccak160_pair(&leaves[0], &leaves[1]);
+        let root = keccak160_pair(&left, &leaves[2]);
+        assert_eq!(merkle_root(&leaves, &Keccak160::default()).unwrap(), root);
+
+        let proof = merkle_proof_at(&leaves, 1, &Keccak160::default()).unwrap();
+        assert_eq!(
+            proof,
+            vec![
+                Node {
+                    data: leaves[0],
+                    side: Side::LEFT
+                },
+                Node {
+                    data: leaves[2],
+                    side: Side::RIGHT
+                },
+            ]
+        );
+        assert_eq!(
+            merkle_proof_check(proof, leaves[1], &Keccak160::default()).unwrap(),
+            root
+        );
+    }
+
+    #[test]
+    fn test_truncated_nodes_go_through_the_inner_hasher() {
+        let hasher = TruncatedHasher(SortedPairHasher(Keccak256Hasher));
+        let leaves = make_leaves(4);
+        let (a, b) = (leaves[0].min(leaves[1]), leaves[0].max(leaves[1]));
+
+        let mut node: Leaf<20> = [0u8; 20];
+        hasher.hash_nodes(&b, &a, &mut node);
+        assert_eq!(node, keccak160_pair(&a, &b));
+
+        // sibling order is ignored, whichever side the proof gives
+        let tree = MerkleTree::new_with_hasher(leaves.clone(), hasher).unwrap();
+        let mut proof = tree.make_proof_at(2).unwrap();
+        for node in &mut proof {
+            node.side = match node.side {
+                Side::LEFT => Side::RIGHT,
+                Side::RIGHT => Side::LEFT,
+            };
+        }
+        assert_eq!(
+            merkle_proof_check(proof, leaves[2], &hasher).unwrap(),
+            tree.root()
+        );
+    }
+
+    #[test]
+    fn test_tree_of_20_byte_hashes() {
+        let leaves = make_leaves(9);
+        let options = TreeOptions {
+            remove_mode: RemoveMode::Tombstone([0xff; 20]),
+            ..Default::default()
+        };
+        let mut tree: MerkleTree<Keccak160, 20> =
+            MerkleTree::new_with_options(leaves[..5].to_vec(), Keccak160::default(), options)
+                .unwrap();
+        let old_root = tree.root();
+
+        tree.extend(leaves[5..].iter().copied());
+        assert_eq!(
+            tree.root(),
+            merkle_root(&leaves, &Keccak160::default()).unwrap()
+        );
+
+        let proof = tree.make_consistency_proof(5).unwrap();
+        assert!(merkle_consistency_check(
+            &proof,
+            5,
+            9,
+            old_root,
+            tree.root(),
+            &Keccak160::default()
+        )
+        .is_ok());
+
+        let root = tree.remove(3).unwrap();
+        let mut expected = leaves.clone();
+        expected[3] = [0xff; 20];
+        assert_eq!(root, merkle_root(&expected, &Keccak160::default()).unwrap());
+
+        for (index, leaf) in expected.iter().enumerate() {
+            let proof = tree.make_proof_at(index).unwrap();
+            assert_eq!(tree.check_proof(proof, *leaf).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn test_sorted_pair_multiproof_of_20_byte_hashes() {
+        let hasher = SortedPairHasher(Keccak160::default());
+        let leaves = make_leaves(12);
+        let tree = MerkleTree::new_with_hasher(leaves, hasher).unwrap();
+
+        let multiproof = tree.make_multiproof(&[1, 4, 7, 11]).unwrap();
+        let root = merkle_multiproof_check(
+            &multiproof.proof,
+            &multiproof.proof_flags,
+            &multiproof.leaves,
+            &hasher,
+        )
+        .unwrap();
+        assert_eq!(root, tree.root());
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_sha512() {
+        use merkletreers::{DomainSeparatedHasher, Sha512Hasher};
+
+        let mut buffer = [0u8; 64];
+        Sha512Hasher.hash(b"abc", &mut buffer);
+        assert_eq!(
+            hex::encode(buffer),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+
+        let leaves = hash_leaves(&["a", "b", "c"], &Sha512Hasher);
+        let tree = MerkleTree::new_with_hasher(leaves.clone(), Sha512Hasher).unwrap();
+        assert_eq!(
+            hex::encode(tree.root()),
+            "b0bc8a48446e6ab7c28d3e2229b0380c35f1b5b8b3221cd2567d121cdd9dd952\
+             a02b139574345cb2fc0ba841d413f508154de1aee996bc8bde468f2d8e3fa063"
+        );
+
+        let proof = tree.make_proof(leaves[2]).unwrap();
+        assert_eq!(tree.check_proof(proof, leaves[2]).unwrap(), tree.root());
+
+        // 0x01 || left || right is 129 bytes with 64-byte hashes
+        let hasher = DomainSeparatedHasher::new(Sha512Hasher);
+        let mut node = [0u8; 64];
+        hasher.hash_nodes(&leaves[0], &leaves[1], &mut node);
+        Sha512Hasher.hash(
+            &[&[0x01], &leaves[0][..], &leaves[1][..]].concat(),
+            &mut buffer,
+        );
+        assert_eq!(node, buffer);
+    }
+
+    #[cfg(feature = "blake2")]
+    #[test]
+    fn test_blake2b_512() {
+        use merkletreers::Blake2b512Hasher;
+
+        let mut buffer = [0u8; 64];
+        Blake2b512Hasher.hash(b"abc", &mut buffer);
+        assert_eq!(
+            hex::encode(buffer),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+
+        let leaves = hash_leaves(&["a", "b", "c"], &Blake2b512Hasher);
+        assert_eq!(
+            hex::encode(merkle_root(&leaves, &Blake2b512Hasher).unwrap()),
+            "f149ffeeb473588bd01e368c64c66102d0756b3438e4fb18d4a6c8aeb9298c85\
+             a9e8bf99684ff0c96328d076ab04b1b6fc6c2870ef2d88468c827d26bdfffe7f"
+        );
+    }
+}
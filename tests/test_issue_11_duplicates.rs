@@ -5,9 +5,10 @@
 /// 1. How are the proofs generated?
 /// 2. How is the root generated?
 /// 3. What happens when we try to make a proof for a duplicate leaf?
-
+use merkletreers::merkle_proof::merkle_proof_at;
 use merkletreers::tree::MerkleTree;
 use merkletreers::utils::hash_it;
+use merkletreers::MerkleError;
 
 #[cfg(test)]
 mod tests {
@@ -29,11 +30,11 @@ mod tests {
             .collect::<Vec<[u8; 32]>>();
 
         // Create tree with duplicate leaves
-        let tree = MerkleTree::new(leaves.clone());
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
 
         // Root should be generated successfully
-        println!("Root with duplicates: {:?}", tree.root);
-        assert_ne!(tree.root, [0u8; 32], "Root should not be zero");
+        println!("Root with duplicates: {:?}", tree.root());
+        assert_ne!(tree.root(), [0u8; 32], "Root should not be zero");
 
         // Let's also test with a smaller example for clarity
         let simple_duplicates = ["a", "b", "a", "c"];
@@ -46,9 +47,13 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let simple_tree = MerkleTree::new(simple_leaves);
-        println!("Simple root with duplicates: {:?}", simple_tree.root);
-        assert_ne!(simple_tree.root, [0u8; 32], "Simple root should not be zero");
+        let simple_tree = MerkleTree::new(simple_leaves).unwrap();
+        println!("Simple root with duplicates: {:?}", simple_tree.root());
+        assert_ne!(
+            simple_tree.root(),
+            [0u8; 32],
+            "Simple root should not be zero"
+        );
     }
 
     #[test]
@@ -65,22 +70,22 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let tree = MerkleTree::new(leaves.clone());
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
 
         // Hash of "a"
         let mut leaf_a = [0u8; 32];
         hash_it("a".as_bytes(), &mut leaf_a);
 
         // Make proof for "a" - this will find the FIRST occurrence at index 0
-        let proof = tree.make_proof(leaf_a);
+        let proof = tree.make_proof(leaf_a).unwrap();
 
         println!("Proof for duplicate 'a': {:?}", proof);
 
         // Save root before check_proof consumes tree
-        let expected_root = tree.root;
+        let expected_root = tree.root();
 
         // Verify the proof
-        let computed_root = tree.check_proof(proof, leaf_a);
+        let computed_root = tree.check_proof(proof, leaf_a).unwrap();
         println!("Computed root: {:?}", computed_root);
         println!("Expected root: {:?}", expected_root);
 
@@ -108,7 +113,7 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let tree = MerkleTree::new(leaves.clone());
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
 
         // Hash of "a"
         let mut leaf_a = [0u8; 32];
@@ -116,16 +121,16 @@ mod tests {
 
         // Try to make proof for "a"
         // The current implementation will find the FIRST occurrence at index 0
-        let proof = tree.make_proof(leaf_a);
+        let proof = tree.make_proof(leaf_a).unwrap();
 
         println!("Number of proof nodes: {}", proof.len());
         println!("Proof for first 'a': {:?}", proof);
 
         // Save root before check_proof consumes tree
-        let expected_root = tree.root;
+        let expected_root = tree.root();
 
         // Verify the proof
-        let computed_root = tree.check_proof(proof.clone(), leaf_a);
+        let computed_root = tree.check_proof(proof.clone(), leaf_a).unwrap();
         println!("Computed root: {:?}", computed_root);
         println!("Expected root: {:?}", expected_root);
 
@@ -153,7 +158,7 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let unique_tree = MerkleTree::new(unique_leaves.clone());
+        let unique_tree = MerkleTree::new(unique_leaves.clone()).unwrap();
 
         // Duplicate leaves (same data but "a" appears twice)
         let duplicate_data = ["a", "b", "a", "d"];
@@ -166,16 +171,17 @@ mod tests {
             })
             .collect::<Vec<[u8; 32]>>();
 
-        let duplicate_tree = MerkleTree::new(duplicate_leaves.clone());
+        let duplicate_tree = MerkleTree::new(duplicate_leaves.clone()).unwrap();
 
         // Roots should be different because the tree structure is different
         assert_ne!(
-            unique_tree.root, duplicate_tree.root,
+            unique_tree.root(),
+            duplicate_tree.root(),
             "Different leaf arrangements should produce different roots"
         );
 
-        println!("Unique tree root: {:?}", unique_tree.root);
-        println!("Duplicate tree root: {:?}", duplicate_tree.root);
+        println!("Unique tree root: {:?}", unique_tree.root());
+        println!("Duplicate tree root: {:?}", duplicate_tree.root());
     }
 
     #[test]
@@ -194,10 +200,10 @@ mod tests {
 
         println!("Number of leaves: {}", leaves.len());
 
-        let tree = MerkleTree::new(leaves.clone());
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
 
-        println!("Root: {:?}", tree.root);
-        let expected_root = tree.root;
+        println!("Root: {:?}", tree.root());
+        let expected_root = tree.root();
 
         // Try to make proof for each unique letter
         let unique_letters = ["m", "e", "r", "k", "l", "t", "s"];
@@ -206,11 +212,11 @@ mod tests {
             let mut leaf = [0u8; 32];
             hash_it(letter.as_bytes(), &mut leaf);
 
-            let tree_for_proof = MerkleTree::new(leaves.clone());
-            let proof = tree_for_proof.make_proof(leaf);
+            let tree_for_proof = MerkleTree::new(leaves.clone()).unwrap();
+            let proof = tree_for_proof.make_proof(leaf).unwrap();
 
-            let tree_for_check = MerkleTree::new(leaves.clone());
-            let computed_root = tree_for_check.check_proof(proof.clone(), leaf);
+            let tree_for_check = MerkleTree::new(leaves.clone()).unwrap();
+            let computed_root = tree_for_check.check_proof(proof.clone(), leaf).unwrap();
 
             println!(
                 "Letter '{}': proof length = {}, verification = {}",
@@ -226,4 +232,60 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_duplicate_leaves_proof_at_every_occurrence() {
+        let data = ["a", "b", "c", "a", "d", "a"];
+
+        let leaves = data
+            .iter()
+            .map(|d| {
+                let mut buffer = [0u8; 32];
+                hash_it(d.as_bytes(), &mut buffer);
+                buffer
+            })
+            .collect::<Vec<[u8; 32]>>();
+
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+        let mut leaf_a = [0u8; 32];
+        hash_it("a".as_bytes(), &mut leaf_a);
+
+        let indices = tree.leaf_indices(leaf_a);
+        assert_eq!(indices, vec![0, 3, 5]);
+
+        let proofs = indices
+            .iter()
+            .map(|&i| tree.make_proof_at(i).unwrap())
+            .collect::<Vec<_>>();
+
+        // Each occurrence gets its own proof, and every one of them is valid
+        assert_ne!(proofs[0], proofs[1]);
+        assert_ne!(proofs[1], proofs[2]);
+        assert_eq!(proofs[0], tree.make_proof(leaf_a).unwrap());
+
+        for (proof, &index) in proofs.iter().zip(&indices) {
+            assert_eq!(
+                proof,
+                &merkle_proof_at(&leaves, index, tree.hasher()).unwrap()
+            );
+            assert_eq!(
+                tree.check_proof(proof.clone(), leaf_a).unwrap(),
+                tree.root()
+            );
+        }
+    }
+
+    #[test]
+    fn test_proof_at_out_of_range() {
+        let mut leaf = [0u8; 32];
+        hash_it("a".as_bytes(), &mut leaf);
+
+        let tree = MerkleTree::new(vec![leaf, leaf]).unwrap();
+
+        assert_eq!(
+            tree.make_proof_at(2),
+            Err(MerkleError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
 }
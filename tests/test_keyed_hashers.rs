@@ -0,0 +1,137 @@
+use merkletreers::hasher::{Hashable, Keccak256Hasher};
+use merkletreers::merkle_proof_check::merkle_proof_check;
+use merkletreers::tree::MerkleTree;
+use merkletreers::utils::{hash_it, hash_leaves};
+use merkletreers::{DomainSeparatedHasher, Leaf, SaltedHasher, TreeOptions};
+
+const EMAILS: [&str; 5] = [
+    "alice@example.com",
+    "bob@example.com",
+    "carol@example.com",
+    "dave@example.com",
+    "eve@example.com",
+];
+
+fn make_tree<H: Hashable>(hasher: H) -> MerkleTree<H> {
+    MerkleTree::new_from_data(&EMAILS, hasher, TreeOptions::default()).unwrap()
+}
+
+#[cfg(any(feature = "hmac", feature = "blake3"))]
+fn digest<H: Hashable>(hasher: &H, data: &[u8]) -> String {
+    let mut buffer = [0u8; 32];
+    hasher.hash(data, &mut buffer);
+    hex::encode(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salted_leaves() {
+        let hasher = SaltedHasher::new(Keccak256Hasher, b"pepper");
+
+        let leaves: Vec<Leaf> = hash_leaves(&EMAILS, &hasher);
+        let mut expected = [0u8; 32];
+        hash_it(b"pepperalice@example.com", &mut expected);
+        assert_eq!(leaves[0], expected);
+
+        // the bare hash of a guessed email is not a leaf any more
+        let mut guess = [0u8; 32];
+        hash_it(b"alice@example.com", &mut guess);
+        assert!(!leaves.contains(&guess));
+
+        let tree = make_tree(hasher.clone());
+        assert_ne!(
+            tree.root(),
+            make_tree(SaltedHasher::new(Keccak256Hasher, b"salt")).root()
+        );
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.make_proof_at(index).unwrap();
+            assert_eq!(
+                merkle_proof_check(proof, *leaf, &hasher).unwrap(),
+                tree.root()
+            );
+        }
+    }
+
+    #[test]
+    fn test_salt_goes_inside_the_leaf_prefix() {
+        let hasher = SaltedHasher::new(DomainSeparatedHasher::new(Keccak256Hasher), b"pepper");
+
+        let mut leaf = [0u8; 32];
+        hasher.hash_leaf(b"alice@example.com", &mut leaf);
+        let mut expected = [0u8; 32];
+        hash_it(b"\x00pepperalice@example.com", &mut expected);
+        assert_eq!(leaf, expected);
+
+        // nodes keep the 0x01 prefix of the inner hasher
+        let mut node = [0u8; 32];
+        hasher.hash_nodes(&leaf, &leaf, &mut node);
+        hash_it(&[&[0x01], &leaf[..], &leaf[..]].concat(), &mut expected);
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn test_debug_hides_the_salt() {
+        let hasher = SaltedHasher::new(Keccak256Hasher, b"pepper");
+        let debug = format!("{hasher:?}");
+
+        assert_eq!(debug, "SaltedHasher { inner: Keccak256Hasher, .. }");
+    }
+
+    #[cfg(feature = "hmac")]
+    #[test]
+    fn test_hmac_sha256() {
+        use merkletreers::HmacSha256Hasher;
+
+        // RFC 4231, test case 2
+        let hasher = HmacSha256Hasher::new(b"Jefe");
+        assert_eq!(
+            digest(&hasher, b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(format!("{hasher:?}"), "HmacSha256Hasher { .. }");
+
+        let tree = make_tree(hasher.clone());
+        assert_ne!(
+            tree.root(),
+            make_tree(HmacSha256Hasher::new(b"Jeff")).root()
+        );
+
+        let leaf = tree.hash_leaf(EMAILS[3].as_bytes());
+        let proof = tree.make_proof_at(3).unwrap();
+        assert_eq!(
+            merkle_proof_check(proof, leaf, &hasher).unwrap(),
+            tree.root()
+        );
+    }
+
+    #[cfg(feature = "blake3")]
+    #[test]
+    fn test_keyed_blake3() {
+        use merkletreers::KeyedBlake3Hasher;
+
+        // keyed_hash of the empty input in test_vectors.json of the BLAKE3 reference
+        let key = *b"whats the Elvish word for friend";
+        let hasher = KeyedBlake3Hasher::new(key);
+        assert_eq!(
+            digest(&hasher, b""),
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26"
+        );
+        assert_eq!(format!("{hasher:?}"), "KeyedBlake3Hasher { .. }");
+
+        let tree = make_tree(hasher.clone());
+        assert_ne!(
+            tree.root(),
+            make_tree(KeyedBlake3Hasher::new([0u8; 32])).root()
+        );
+
+        let leaf = tree.hash_leaf(EMAILS[1].as_bytes());
+        let proof = tree.make_proof_at(1).unwrap();
+        assert_eq!(
+            merkle_proof_check(proof, leaf, &hasher).unwrap(),
+            tree.root()
+        );
+    }
+}
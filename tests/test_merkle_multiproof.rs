@@ -0,0 +1,161 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::merkle_multiproof::{merkle_multiproof, MultiProof};
+use merkletreers::merkle_multiproof_check::merkle_multiproof_check;
+use merkletreers::tree::MerkleTree;
+use merkletreers::{Keccak256Hasher, MerkleError, SortedPairHasher};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiproof_layout() {
+        let leaves = make_leaves(4);
+        let tree =
+            MerkleTree::new_with_hasher(leaves.clone(), SortedPairHasher(Keccak256Hasher)).unwrap();
+
+        assert_eq!(
+            tree.make_multiproof(&[3, 0]).unwrap(),
+            MultiProof {
+                leaves: vec![leaves[0], leaves[3]],
+                proof: vec![leaves[1], leaves[2]],
+                proof_flags: vec![false, false, true],
+            }
+        );
+        assert_eq!(
+            tree.make_multiproof(&[0, 1]).unwrap(),
+            MultiProof {
+                leaves: vec![leaves[0], leaves[1]],
+                proof: vec![tree.layers()[1][1]],
+                proof_flags: vec![true, false],
+            }
+        );
+    }
+
+    #[test]
+    fn test_multiproof_every_subset_of_power_of_two_tree() {
+        for size in [1, 2, 4, 8] {
+            let leaves = make_leaves(size);
+            let tree =
+                MerkleTree::new_with_hasher(leaves.clone(), SortedPairHasher(Keccak256Hasher))
+                    .unwrap();
+
+            for mask in 0u32..(1 << size) {
+                let indices: Vec<usize> = (0..size).filter(|i| mask & (1 << i) != 0).collect();
+                let multiproof = tree.make_multiproof(&indices).unwrap();
+
+                assert_eq!(
+                    multiproof.leaves,
+                    indices.iter().map(|&i| leaves[i]).collect::<Vec<_>>()
+                );
+                assert_eq!(tree.check_multiproof(&multiproof).unwrap(), tree.root());
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiproof_odd_tree() {
+        for size in [3, 5, 6, 7, 9, 11] {
+            let leaves = make_leaves(size);
+            let tree =
+                MerkleTree::new_with_hasher(leaves.clone(), SortedPairHasher(Keccak256Hasher))
+                    .unwrap();
+
+            for mask in 1u32..(1 << size) {
+                let indices: Vec<usize> = (0..size).filter(|i| mask & (1 << i) != 0).collect();
+
+                match merkle_multiproof(&leaves, &indices, &SortedPairHasher(Keccak256Hasher)) {
+                    Ok(multiproof) => assert_eq!(
+                        merkle_multiproof_check(
+                            &multiproof.proof,
+                            &multiproof.proof_flags,
+                            &multiproof.leaves,
+                            &SortedPairHasher(Keccak256Hasher)
+                        )
+                        .unwrap(),
+                        tree.root()
+                    ),
+                    Err(error) => assert_eq!(error, MerkleError::UnsupportedMultiProof),
+                }
+            }
+
+            // a single leaf is always provable
+            for index in 0..size {
+                let multiproof = tree.make_multiproof(&[index]).unwrap();
+                assert_eq!(tree.check_multiproof(&multiproof).unwrap(), tree.root());
+            }
+        }
+    }
+
+    #[test]
+    fn test_multiproof_promoted_leaf_past_computed_node() {
+        let tree =
+            MerkleTree::new_with_hasher(make_leaves(5), SortedPairHasher(Keccak256Hasher)).unwrap();
+
+        assert_eq!(
+            tree.make_multiproof(&[0, 4]),
+            Err(MerkleError::UnsupportedMultiProof)
+        );
+    }
+
+    #[test]
+    fn test_multiproof_without_commutative_hasher() {
+        let tree = MerkleTree::new(make_leaves(4)).unwrap();
+
+        // every pair is hashed left first, no side is needed
+        let multiproof = tree.make_multiproof(&[0, 1, 2, 3]).unwrap();
+        assert_eq!(tree.check_multiproof(&multiproof).unwrap(), tree.root());
+
+        // the sibling of leaf 1 is on its left
+        assert_eq!(
+            tree.make_multiproof(&[1]),
+            Err(MerkleError::UnsupportedMultiProof)
+        );
+    }
+
+    #[test]
+    fn test_multiproof_empty_and_invalid() {
+        let tree =
+            MerkleTree::new_with_hasher(make_leaves(4), SortedPairHasher(Keccak256Hasher)).unwrap();
+
+        let multiproof = tree.make_multiproof(&[]).unwrap();
+        assert_eq!(multiproof.proof, vec![tree.root()]);
+        assert_eq!(tree.check_multiproof(&multiproof).unwrap(), tree.root());
+
+        assert_eq!(
+            tree.make_multiproof(&[4]),
+            Err(MerkleError::IndexOutOfRange { index: 4, len: 4 })
+        );
+
+        let mut multiproof = tree.make_multiproof(&[0, 2]).unwrap();
+        multiproof.proof_flags.push(true);
+        assert_eq!(
+            tree.check_multiproof(&multiproof),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_multiproof_check_wrong_number_of_proof_hashes() {
+        let hasher = SortedPairHasher(Keccak256Hasher);
+        let leaves = make_leaves(3);
+
+        // too few: two flags ask for proof hashes that are not there
+        assert_eq!(
+            merkle_multiproof_check(&[], &[false, false], &leaves, &hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        // too many: one more hash than the flags consume
+        let tree = MerkleTree::new_with_hasher(make_leaves(4), hasher).unwrap();
+        let multiproof = tree.make_multiproof(&[0, 2]).unwrap();
+        let mut proof = multiproof.proof.clone();
+        proof.push(leaves[0]);
+        assert_eq!(
+            merkle_multiproof_check(&proof, &multiproof.proof_flags, &multiproof.leaves, &hasher),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+}
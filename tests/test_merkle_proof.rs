@@ -61,7 +61,7 @@ mod tests {
 
         #[test]
         fn merkle_proof_leaves_even_make_proof() {
-            let result = merkle_proof(&SETUP_LEAVES, SETUP_LEAF, &Keccak256Hasher);
+            let result = merkle_proof(&SETUP_LEAVES, SETUP_LEAF, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_PROOF.to_vec());
         }
@@ -124,7 +124,7 @@ mod tests {
 
         #[test]
         fn merkle_proof_leaves_even_make_proof() {
-            let result = merkle_proof(&SETUP_LEAVES, SETUP_LEAF, &Keccak256Hasher);
+            let result = merkle_proof(&SETUP_LEAVES, SETUP_LEAF, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_PROOF.to_vec());
         }
@@ -180,7 +180,7 @@ mod tests {
 
         #[test]
         fn merkle_proof_leaves_base_2_make_proof() {
-            let result = merkle_proof(&SETUP_LEAVES, SETUP_LEAF, &Keccak256Hasher);
+            let result = merkle_proof(&SETUP_LEAVES, SETUP_LEAF, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_PROOF.to_vec());
         }
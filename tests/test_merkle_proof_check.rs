@@ -39,7 +39,8 @@ mod tests {
 
         #[test]
         fn merkle_proof_check_leaves_even_make_proof() {
-            let result = merkle_proof_check(SETUP_PROOF.to_vec(), SETUP_LEAF, &Keccak256Hasher);
+            let result =
+                merkle_proof_check(SETUP_PROOF.to_vec(), SETUP_LEAF, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_ROOT);
         }
@@ -84,7 +85,8 @@ mod tests {
 
         #[test]
         fn merkle_proof_check_leaves_odd_make_proof() {
-            let result = merkle_proof_check(SETUP_PROOF.to_vec(), SETUP_LEAF, &Keccak256Hasher);
+            let result =
+                merkle_proof_check(SETUP_PROOF.to_vec(), SETUP_LEAF, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_ROOT);
         }
@@ -122,7 +124,8 @@ mod tests {
 
         #[test]
         fn merkle_proof_check_leaves_base_2_make_proof() {
-            let result = merkle_proof_check(SETUP_PROOF.to_vec(), SETUP_LEAF, &Keccak256Hasher);
+            let result =
+                merkle_proof_check(SETUP_PROOF.to_vec(), SETUP_LEAF, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_ROOT);
         }
@@ -1,5 +1,6 @@
 use merkletreers::hasher::Keccak256Hasher;
 use merkletreers::merkle_root::merkle_root;
+use merkletreers::tree::MerkleTree;
 use merkletreers::{Leaf, Root};
 
 #[cfg(test)]
@@ -43,7 +44,7 @@ mod tests {
 
         #[test]
         fn test_merkle_root_leaves_even_make_root() {
-            let result = merkle_root(&SETUP_LEAVES, &Keccak256Hasher);
+            let result = merkle_root(&SETUP_LEAVES, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_ROOT);
         }
@@ -82,7 +83,7 @@ mod tests {
 
         #[test]
         fn test_merkle_root_leaves_odd_make_root() {
-            let result = merkle_root(&SETUP_LEAVES, &Keccak256Hasher);
+            let result = merkle_root(&SETUP_LEAVES, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_ROOT);
         }
@@ -121,9 +122,30 @@ mod tests {
 
         #[test]
         fn test_merkle_root_leaves_base_2_make_root() {
-            let result = merkle_root(&SETUP_LEAVES, &Keccak256Hasher);
+            let result = merkle_root(&SETUP_LEAVES, &Keccak256Hasher).unwrap();
 
             assert_eq!(result, SETUP_ROOT);
         }
     }
+
+    mod merkle_root_single_leaf {
+        use super::*;
+
+        // keccak256(a)
+        const SETUP_LEAF: Leaf = [
+            58, 194, 37, 22, 141, 245, 66, 18, 162, 92, 28, 1, 253, 53, 190, 191, 234, 64, 143,
+            218, 194, 227, 29, 221, 111, 128, 164, 187, 249, 165, 241, 203,
+        ];
+
+        /// 1.x returned `[0u8; 32]` here, 2.0 returns the leaf itself
+        #[test]
+        fn test_merkle_root_single_leaf_is_the_leaf() {
+            let result = merkle_root(&[SETUP_LEAF], &Keccak256Hasher).unwrap();
+            let tree = MerkleTree::new(vec![SETUP_LEAF]).unwrap();
+
+            assert_eq!(result, SETUP_LEAF);
+            assert_eq!(tree.root(), SETUP_LEAF);
+            assert_ne!(result, [0u8; 32]);
+        }
+    }
 }
@@ -0,0 +1,418 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::hasher::{Hashable, Keccak256Hasher};
+use merkletreers::merkle_root::merkle_root;
+use merkletreers::mmr::{
+    leaf_index_to_pos, peak_positions, update_mmr_proof, verify_ancestry_proof, verify_mmr_proof,
+    FileStore, MemoryStore, Mmr, MmrError, MmrProof,
+};
+use merkletreers::utils::hash_leaves;
+use merkletreers::{Leaf, MerkleError, TruncatedHasher};
+
+fn hash_pair(left: &Leaf, right: &Leaf) -> Leaf {
+    let mut buffer = [0u8; 32];
+    Keccak256Hasher.hash_nodes(left, right, &mut buffer);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let positions: Vec<u64> = (0..7).map(leaf_index_to_pos).collect();
+        assert_eq!(positions, vec![0, 1, 3, 4, 7, 8, 10]);
+
+        assert_eq!(peak_positions(0), Some(vec![]));
+        assert_eq!(peak_positions(11), Some(vec![6, 9, 10]));
+        assert_eq!(peak_positions(15), Some(vec![14]));
+        // a parent is missing
+        assert_eq!(peak_positions(5), None);
+    }
+
+    #[test]
+    fn test_root_by_hand() {
+        let l = make_leaves(7);
+        let mut mmr = Mmr::new();
+        assert_eq!(mmr.root().unwrap(), [0u8; 32]);
+
+        for leaf in &l {
+            mmr.append(*leaf).unwrap();
+        }
+
+        let left = hash_pair(&hash_pair(&l[0], &l[1]), &hash_pair(&l[2], &l[3]));
+        let middle = hash_pair(&l[4], &l[5]);
+        assert_eq!(mmr.size(), 11);
+        assert_eq!(mmr.leaf_count(), 7);
+        assert_eq!(mmr.peaks().unwrap(), vec![left, middle, l[6]]);
+        assert_eq!(
+            mmr.root().unwrap(),
+            hash_pair(&left, &hash_pair(&middle, &l[6]))
+        );
+    }
+
+    #[test]
+    fn test_single_peak_matches_merkle_root() {
+        let leaves = make_leaves(64);
+        let mut mmr = Mmr::new();
+
+        for (count, leaf) in leaves.iter().enumerate() {
+            mmr.append(*leaf).unwrap();
+
+            if (count + 1).is_power_of_two() {
+                assert_eq!(
+                    mmr.root().unwrap(),
+                    merkle_root(&leaves[..=count], &Keccak256Hasher).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_every_proof_verifies() {
+        let leaves = make_leaves(40);
+        let mut mmr = Mmr::new();
+
+        for (count, leaf) in leaves.iter().enumerate() {
+            assert_eq!(mmr.append(*leaf).unwrap(), count as u64);
+
+            for (index, leaf) in leaves[..=count].iter().enumerate() {
+                let proof = mmr.make_proof(index as u64).unwrap();
+                assert!(mmr.check_proof(&proof, *leaf, index as u64).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_proofs_are_rejected() {
+        let leaves = make_leaves(11);
+        let mut mmr = Mmr::new();
+        for leaf in &leaves {
+            mmr.append(*leaf).unwrap();
+        }
+        let root = mmr.root().unwrap();
+        let proof = mmr.make_proof(5).unwrap();
+
+        assert_eq!(
+            verify_mmr_proof(&proof, leaves[4], 5, root, &Keccak256Hasher),
+            Err(MerkleError::RootMismatch)
+        );
+        assert_eq!(
+            verify_mmr_proof(&proof, leaves[5], 4, root, &Keccak256Hasher),
+            Err(MerkleError::RootMismatch)
+        );
+
+        let mut extra = proof.clone();
+        extra.siblings.push(leaves[0]);
+        assert_eq!(
+            verify_mmr_proof(&extra, leaves[5], 5, root, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        let mut truncated = proof.clone();
+        truncated.siblings.pop();
+        assert_eq!(
+            verify_mmr_proof(&truncated, leaves[5], 5, root, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        let mut wrong_size = proof.clone();
+        wrong_size.mmr_size = 12;
+        assert_eq!(
+            verify_mmr_proof(&wrong_size, leaves[5], 5, root, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        assert!(matches!(
+            mmr.make_proof(11),
+            Err(MmrError::Merkle(MerkleError::IndexOutOfRange {
+                index: 11,
+                len: 11
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_mmr_sizes_do_not_overflow() {
+        // u64::MAX nodes is a single perfect tree of height 63
+        assert_eq!(peak_positions(u64::MAX), Some(vec![u64::MAX - 1]));
+        assert_eq!(peak_positions(u64::MAX - 1), None);
+
+        let leaf = make_leaves(1)[0];
+        let last = (1 << 63) - 1;
+        let proof = MmrProof {
+            mmr_size: u64::MAX,
+            siblings: vec![leaf; 63],
+            peaks: vec![],
+        };
+        assert!(proof.root(leaf, 0, &Keccak256Hasher).is_ok());
+        assert!(proof.root(leaf, last, &Keccak256Hasher).is_ok());
+
+        let mut extra = proof.clone();
+        extra.siblings.push(leaf);
+        assert_eq!(
+            extra.root(leaf, last, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+        assert_eq!(
+            proof.root(leaf, last + 1, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_every_ancestry_proof_verifies() {
+        let leaves = make_leaves(24);
+        let mut mmr = Mmr::new();
+        let mut history = vec![(mmr.size(), mmr.root().unwrap())];
+        for leaf in &leaves {
+            mmr.append(*leaf).unwrap();
+            history.push((mmr.size(), mmr.root().unwrap()));
+        }
+
+        let mut newer = Mmr::new();
+        for (count, &(new_size, new_root)) in history.iter().enumerate().skip(1) {
+            newer.append(leaves[count - 1]).unwrap();
+
+            for &(old_size, old_root) in &history[..=count] {
+                let proof = newer.make_ancestry_proof(old_size).unwrap();
+                assert_eq!(proof.new_size, new_size);
+                assert!(
+                    verify_ancestry_proof(&proof, old_root, new_root, &Keccak256Hasher).is_ok()
+                );
+                assert!(newer.check_ancestry_proof(&proof, old_root).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_ancestry_proofs_are_rejected() {
+        let leaves = make_leaves(13);
+        let mut mmr = Mmr::new();
+        for leaf in &leaves[..6] {
+            mmr.append(*leaf).unwrap();
+        }
+        let (old_size, old_root) = (mmr.size(), mmr.root().unwrap());
+        for leaf in &leaves[6..] {
+            mmr.append(*leaf).unwrap();
+        }
+        let new_root = mmr.root().unwrap();
+        let proof = mmr.make_ancestry_proof(old_size).unwrap();
+
+        assert_eq!(
+            verify_ancestry_proof(&proof, new_root, new_root, &Keccak256Hasher),
+            Err(MerkleError::OldRootMismatch)
+        );
+        assert_eq!(
+            verify_ancestry_proof(&proof, old_root, old_root, &Keccak256Hasher),
+            Err(MerkleError::NewRootMismatch)
+        );
+
+        let mut tampered = proof.clone();
+        tampered.nodes[0] = leaves[0];
+        assert_eq!(
+            verify_ancestry_proof(&tampered, old_root, new_root, &Keccak256Hasher),
+            Err(MerkleError::NewRootMismatch)
+        );
+
+        let mut extra = proof.clone();
+        extra.nodes.push(leaves[0]);
+        assert_eq!(
+            verify_ancestry_proof(&extra, old_root, new_root, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        let mut truncated = proof.clone();
+        truncated.nodes.pop();
+        assert_eq!(
+            verify_ancestry_proof(&truncated, old_root, new_root, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        let mut wrong_size = proof.clone();
+        wrong_size.old_size = 9;
+        assert_eq!(
+            verify_ancestry_proof(&wrong_size, old_root, new_root, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        // 5 nodes is not the size of any MMR
+        assert!(matches!(
+            mmr.make_ancestry_proof(5),
+            Err(MmrError::InvalidSize(5))
+        ));
+        assert!(matches!(
+            mmr.make_ancestry_proof(mmr.size() + 1),
+            Err(MmrError::InvalidSize(_))
+        ));
+    }
+
+    #[test]
+    fn test_updated_proofs_match_new_proofs() {
+        let leaves = make_leaves(20);
+        let mut old = Mmr::new();
+
+        for old_count in 1..leaves.len() {
+            old.append(leaves[old_count - 1]).unwrap();
+            let mut new = Mmr::new();
+            for leaf in &leaves {
+                new.append(*leaf).unwrap();
+            }
+            let ancestry = new.make_ancestry_proof(old.size()).unwrap();
+
+            for (index, leaf) in leaves[..old_count].iter().enumerate() {
+                let index = index as u64;
+                let proof = old.make_proof(index).unwrap();
+                let updated = update_mmr_proof(&proof, index, &ancestry, &Keccak256Hasher).unwrap();
+
+                assert_eq!(updated, new.make_proof(index).unwrap());
+                assert!(new.check_proof(&updated, *leaf, index).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_update_rejects_mismatched_proofs() {
+        let leaves = make_leaves(11);
+        let mut mmr = Mmr::new();
+        for leaf in &leaves[..7] {
+            mmr.append(*leaf).unwrap();
+        }
+        let old_size = mmr.size();
+        let proof = mmr.make_proof(2).unwrap();
+        for leaf in &leaves[7..] {
+            mmr.append(*leaf).unwrap();
+        }
+        let ancestry = mmr.make_ancestry_proof(old_size).unwrap();
+
+        // made against another size
+        let newer_proof = mmr.make_proof(2).unwrap();
+        assert_eq!(
+            update_mmr_proof(&newer_proof, 2, &ancestry, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        // made against other peaks
+        let mut other_peaks = proof.clone();
+        other_peaks.peaks[0] = leaves[0];
+        assert_eq!(
+            update_mmr_proof(&other_peaks, 2, &ancestry, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+
+        assert_eq!(
+            update_mmr_proof(&proof, 7, &ancestry, &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mmr");
+        let leaves = make_leaves(25);
+
+        let mut memory = Mmr::new_with_hasher(Keccak256Hasher);
+        let mut file =
+            Mmr::new_with_store(FileStore::open(&path).unwrap(), Keccak256Hasher).unwrap();
+        for leaf in &leaves[..13] {
+            memory.append(*leaf).unwrap();
+            file.append(*leaf).unwrap();
+        }
+        file.store().sync().unwrap();
+        drop(file);
+
+        let mut file =
+            Mmr::new_with_store(FileStore::open(&path).unwrap(), Keccak256Hasher).unwrap();
+        assert_eq!(file.root().unwrap(), memory.root().unwrap());
+        for leaf in &leaves[13..] {
+            memory.append(*leaf).unwrap();
+            file.append(*leaf).unwrap();
+        }
+
+        assert_eq!(file.root().unwrap(), memory.root().unwrap());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 32 * file.size());
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = file.make_proof(index as u64).unwrap();
+            assert_eq!(proof, memory.make_proof(index as u64).unwrap());
+            assert!(file.check_proof(&proof, *leaf, index as u64).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_file_store_concurrent_reads() {
+        let dir = tempfile::tempdir().unwrap();
+        let leaves = make_leaves(500);
+
+        let mut memory = Mmr::new_with_hasher(Keccak256Hasher);
+        let mut file = Mmr::new_with_store(
+            FileStore::open(dir.path().join("mmr")).unwrap(),
+            Keccak256Hasher,
+        )
+        .unwrap();
+        for leaf in &leaves {
+            memory.append(*leaf).unwrap();
+            file.append(*leaf).unwrap();
+        }
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for index in 0..leaves.len() as u64 {
+                        assert_eq!(
+                            file.make_proof(index).unwrap(),
+                            memory.make_proof(index).unwrap()
+                        );
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_20_byte_nodes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mmr");
+        let hasher = TruncatedHasher::<Keccak256Hasher>::default();
+        let data: Vec<String> = (0..8).map(|i| i.to_string()).collect();
+        let leaves: Vec<Leaf<20>> = hash_leaves(&data, &hasher);
+
+        let mut mmr = Mmr::new_with_store(FileStore::open(&path).unwrap(), hasher).unwrap();
+        for leaf in &leaves {
+            mmr.append(*leaf).unwrap();
+        }
+
+        // 8 leaves make a single peak, the root of the plain tree
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 20 * mmr.size());
+        assert_eq!(mmr.root().unwrap(), merkle_root(&leaves, &hasher).unwrap());
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = mmr.make_proof(index as u64).unwrap();
+            assert!(mmr.check_proof(&proof, *leaf, index as u64).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_corrupted_stores_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mmr");
+
+        std::fs::write(&path, [0u8; 33]).unwrap();
+        assert!(matches!(
+            FileStore::<32>::open(&path),
+            Err(MmrError::CorruptedStore)
+        ));
+
+        // 5 nodes is not the size of any MMR
+        std::fs::write(&path, [0u8; 5 * 32]).unwrap();
+        let store = FileStore::open(&path).unwrap();
+        assert!(matches!(
+            Mmr::new_with_store(store, Keccak256Hasher),
+            Err(MmrError::CorruptedStore)
+        ));
+
+        let store = MemoryStore::new();
+        assert!(Mmr::new_with_store(store, Keccak256Hasher).is_ok());
+    }
+}
@@ -0,0 +1,256 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::hasher::{Hashable, Keccak256Hasher};
+use merkletreers::merkle_multiproof::merkle_multiproof_with_options;
+use merkletreers::merkle_multiproof_check::merkle_multiproof_check;
+use merkletreers::merkle_proof::merkle_proof_at_with_options;
+use merkletreers::merkle_proof_check::merkle_proof_check;
+use merkletreers::merkle_root::{merkle_root, merkle_root_with_options};
+use merkletreers::tree::MerkleTree;
+use merkletreers::{Leaf, OddNodeStrategy, SortedPairHasher, TreeOptions};
+
+const STRATEGIES: [OddNodeStrategy; 5] = [
+    OddNodeStrategy::Promote,
+    OddNodeStrategy::Duplicate,
+    OddNodeStrategy::PadWith {
+        leaf: [0u8; 32],
+        depth: None,
+    },
+    OddNodeStrategy::PadWith {
+        leaf: [0xee; 32],
+        depth: None,
+    },
+    OddNodeStrategy::PadWith {
+        leaf: [0u8; 32],
+        depth: Some(6),
+    },
+];
+
+fn options(odd_node: OddNodeStrategy) -> TreeOptions {
+    TreeOptions {
+        odd_node,
+        ..Default::default()
+    }
+}
+
+fn hash_pair(left: &Leaf, right: &Leaf) -> Leaf {
+    let mut buffer = [0u8; 32];
+    Keccak256Hasher.hash_nodes(left, right, &mut buffer);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_hashes_the_last_node_with_itself() {
+        let l = make_leaves(5);
+
+        let expected = hash_pair(
+            &hash_pair(&hash_pair(&l[0], &l[1]), &hash_pair(&l[2], &l[3])),
+            &hash_pair(&hash_pair(&l[4], &l[4]), &hash_pair(&l[4], &l[4])),
+        );
+
+        let root =
+            merkle_root_with_options(&l, &Keccak256Hasher, &options(OddNodeStrategy::Duplicate));
+        assert_eq!(root.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_pad_with_matches_a_padded_tree() {
+        for filler in [[0u8; 32], [0xee; 32]] {
+            for size in 1..=33 {
+                let leaves = make_leaves(size);
+                let mut padded = leaves.clone();
+                padded.resize(size.next_power_of_two(), filler);
+
+                let root = merkle_root_with_options(
+                    &leaves,
+                    &Keccak256Hasher,
+                    &options(OddNodeStrategy::PadWith {
+                        leaf: filler,
+                        depth: None,
+                    }),
+                );
+                assert_eq!(root, merkle_root(&padded, &Keccak256Hasher));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pad_with_depth_matches_a_full_tree() {
+        for (size, depth) in [(5, 16), (1, 16), (1, 5), (9, 5), (32, 5)] {
+            let leaves = make_leaves(size);
+            let mut padded = leaves.clone();
+            padded.resize(1 << depth, [0u8; 32]);
+            let expected = merkle_root(&padded, &Keccak256Hasher).unwrap();
+
+            let strategy = OddNodeStrategy::PadWith {
+                leaf: [0u8; 32],
+                depth: Some(depth),
+            };
+            let tree =
+                MerkleTree::new_with_options(leaves.clone(), Keccak256Hasher, options(strategy))
+                    .unwrap();
+
+            assert_eq!(
+                merkle_root_with_options(&leaves, &Keccak256Hasher, &options(strategy)).unwrap(),
+                expected
+            );
+            assert_eq!(tree.root(), expected);
+            assert_eq!(tree.layers().len(), depth as usize + 1);
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.make_proof_at(index).unwrap();
+                assert_eq!(proof.len(), depth as usize);
+                assert_eq!(
+                    merkle_proof_check(proof, *leaf, &Keccak256Hasher).unwrap(),
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pad_with_depth_below_the_leaves_is_ignored() {
+        let leaves = make_leaves(9);
+        let deep = options(OddNodeStrategy::PadWith {
+            leaf: [0u8; 32],
+            depth: Some(2),
+        });
+        let natural = options(OddNodeStrategy::PadWith {
+            leaf: [0u8; 32],
+            depth: None,
+        });
+
+        assert_eq!(
+            merkle_root_with_options(&leaves, &Keccak256Hasher, &deep),
+            merkle_root_with_options(&leaves, &Keccak256Hasher, &natural)
+        );
+    }
+
+    #[test]
+    fn test_promote_is_the_default() {
+        let leaves = make_leaves(11);
+
+        assert_eq!(
+            merkle_root_with_options(
+                &leaves,
+                &Keccak256Hasher,
+                &options(OddNodeStrategy::Promote)
+            ),
+            merkle_root(&leaves, &Keccak256Hasher)
+        );
+    }
+
+    #[test]
+    fn test_strategies_give_different_roots() {
+        let leaves = make_leaves(5);
+        let roots: Vec<Leaf> = STRATEGIES
+            .iter()
+            .map(|&strategy| {
+                merkle_root_with_options(&leaves, &Keccak256Hasher, &options(strategy)).unwrap()
+            })
+            .collect();
+
+        for (i, root) in roots.iter().enumerate() {
+            assert!(!roots[i + 1..].contains(root));
+        }
+    }
+
+    #[test]
+    fn test_proofs_agree_with_root() {
+        for strategy in STRATEGIES {
+            for size in 1..=20 {
+                let leaves = make_leaves(size);
+                let tree = MerkleTree::new_with_options(
+                    leaves.clone(),
+                    Keccak256Hasher,
+                    options(strategy),
+                )
+                .unwrap();
+
+                assert_eq!(
+                    tree.root(),
+                    merkle_root_with_options(&leaves, &Keccak256Hasher, &options(strategy))
+                        .unwrap()
+                );
+
+                for (index, leaf) in leaves.iter().enumerate() {
+                    let proof = tree.make_proof_at(index).unwrap();
+                    assert_eq!(
+                        proof,
+                        merkle_proof_at_with_options(
+                            &leaves,
+                            index,
+                            &Keccak256Hasher,
+                            &options(strategy)
+                        )
+                        .unwrap()
+                    );
+                    assert_eq!(
+                        merkle_proof_check(proof, *leaf, &Keccak256Hasher).unwrap(),
+                        tree.root()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_updates_match_tree_built_from_scratch() {
+        let leaves = make_leaves(19);
+        let replacements = make_leaves(40);
+
+        for strategy in STRATEGIES {
+            let mut tree = MerkleTree::new_with_options(
+                leaves[..3].to_vec(),
+                Keccak256Hasher,
+                options(strategy),
+            )
+            .unwrap();
+            tree.extend(leaves[3..].iter().copied());
+            tree.set_leaves([(18, replacements[30]), (4, replacements[31])])
+                .unwrap();
+            tree.remove(7).unwrap();
+
+            let mut expected = leaves.clone();
+            expected[18] = replacements[30];
+            expected[4] = replacements[31];
+            expected.remove(7);
+            let expected =
+                MerkleTree::new_with_options(expected, Keccak256Hasher, options(strategy)).unwrap();
+
+            assert_eq!(tree.layers(), expected.layers());
+        }
+    }
+
+    #[test]
+    fn test_multiproofs_follow_the_strategy() {
+        let hasher = SortedPairHasher(Keccak256Hasher);
+        let leaves = make_leaves(7);
+
+        for strategy in STRATEGIES {
+            let root = merkle_root_with_options(&leaves, &hasher, &options(strategy)).unwrap();
+
+            for indices in [vec![0, 6], vec![6], vec![1, 2, 5], vec![4, 5, 6]] {
+                let multiproof =
+                    merkle_multiproof_with_options(&leaves, &indices, &hasher, &options(strategy))
+                        .unwrap();
+
+                assert_eq!(
+                    merkle_multiproof_check(
+                        &multiproof.proof,
+                        &multiproof.proof_flags,
+                        &multiproof.leaves,
+                        &hasher
+                    )
+                    .unwrap(),
+                    root
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Vectors from circomlib's Poseidon over BN254 (also checked by light-poseidon)
+
+#![cfg(feature = "poseidon")]
+
+use merkletreers::hasher::Hashable;
+use merkletreers::merkle_proof_check::merkle_proof_check;
+use merkletreers::tree::MerkleTree;
+use merkletreers::{Leaf, PoseidonHasher};
+
+/// Small integers as big-endian field elements, as circuits take them
+fn make_leaves(count: u64) -> Vec<Leaf> {
+    (1..=count)
+        .map(|value| {
+            let mut leaf = [0u8; 32];
+            leaf[24..].copy_from_slice(&value.to_be_bytes());
+            leaf
+        })
+        .collect()
+}
+
+fn hash_pair(left: &Leaf, right: &Leaf) -> Leaf {
+    let mut buffer = [0u8; 32];
+    PoseidonHasher.hash_nodes(left, right, &mut buffer);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circomlib_vectors() {
+        let leaves = make_leaves(2);
+
+        assert_eq!(
+            hex::encode(hash_pair(&leaves[0], &leaves[1])),
+            "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+        );
+        assert_eq!(
+            hex::encode(hash_pair(&leaves[0], &leaves[0])),
+            "007af346e2d304279e79e0a9f3023f771294a78acb70e73f90afe27cad401e81"
+        );
+
+        let mut buffer = [0u8; 32];
+        PoseidonHasher.hash(&[1], &mut buffer);
+        assert_eq!(
+            hex::encode(buffer),
+            "29176100eaa962bdc1fe6c654d6a3c130e96a4d1168b33848b897dc502820133"
+        );
+    }
+
+    #[test]
+    fn test_children_are_field_elements() {
+        // the field order reduces to zero, like in a circuit
+        let order: Leaf =
+            hex::decode("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let zero = [0u8; 32];
+        assert_eq!(hash_pair(&order, &zero), hash_pair(&zero, &zero));
+
+        // which is why untrusted leaves should be checked first
+        let mut below = order;
+        below[31] -= 1;
+        assert!(PoseidonHasher::is_canonical(&below));
+        assert!(!PoseidonHasher::is_canonical(&order));
+        assert!(!PoseidonHasher::is_canonical(&[0xff; 32]));
+
+        // not a hash of the concatenation
+        let leaves = make_leaves(2);
+        let mut concat = [0u8; 32];
+        PoseidonHasher.hash(&[leaves[0], leaves[1]].concat(), &mut concat);
+        assert_ne!(hash_pair(&leaves[0], &leaves[1]), concat);
+    }
+
+    #[test]
+    fn test_cached_sponges_keep_no_state() {
+        let leaves = make_leaves(2);
+        let expected = hash_pair(&leaves[0], &leaves[1]);
+
+        // interleave both arities, on this thread and on another one
+        let mut buffer = [0u8; 32];
+        for _ in 0..3 {
+            PoseidonHasher.hash(&[1], &mut buffer);
+            assert_eq!(hash_pair(&leaves[0], &leaves[1]), expected);
+        }
+        let other = std::thread::spawn(move || hash_pair(&leaves[0], &leaves[1]));
+        assert_eq!(other.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_tree_matches_circuit_hashing() {
+        let leaves = make_leaves(5);
+        let tree = MerkleTree::new_with_hasher(leaves.clone(), PoseidonHasher).unwrap();
+
+        let left = hash_pair(
+            &hash_pair(&leaves[0], &leaves[1]),
+            &hash_pair(&leaves[2], &leaves[3]),
+        );
+        assert_eq!(tree.root(), hash_pair(&left, &leaves[4]));
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.make_proof_at(index).unwrap();
+            assert_eq!(
+                merkle_proof_check(proof, *leaf, &PoseidonHasher).unwrap(),
+                tree.root()
+            );
+        }
+    }
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::hasher::Keccak256Hasher;
+use merkletreers::merkle_root::merkle_root;
+use merkletreers::tree::MerkleTree;
+use merkletreers::{EmptyRoot, Leaf, MerkleError, RemoveMode, TreeOptions};
+
+fn tree_with_mode(leaves: Vec<Leaf>, remove_mode: RemoveMode) -> MerkleTree {
+    let options = TreeOptions {
+        remove_mode,
+        ..Default::default()
+    };
+    MerkleTree::new_with_options(leaves, Keccak256Hasher, options).unwrap()
+}
+
+fn assert_proofs_valid(tree: &MerkleTree) {
+    for (index, leaf) in tree.leaves().iter().enumerate() {
+        let proof = tree.make_proof_at(index).unwrap();
+        assert_eq!(tree.check_proof(proof, *leaf).unwrap(), tree.root());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact_remove_matches_tree_built_from_scratch() {
+        for size in 1..=20 {
+            for index in 0..size {
+                let mut leaves = make_leaves(size);
+                let mut tree = tree_with_mode(leaves.clone(), RemoveMode::Compact);
+
+                let root = tree.remove(index).unwrap();
+                leaves.remove(index);
+
+                assert_eq!(root, merkle_root(&leaves, &Keccak256Hasher).unwrap());
+                assert_eq!(tree.layers(), MerkleTree::new(leaves).unwrap().layers());
+                assert_proofs_valid(&tree);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_remove_until_empty() {
+        let mut tree = tree_with_mode(make_leaves(9), RemoveMode::Compact);
+
+        while !tree.leaves().is_empty() {
+            tree.remove(0).unwrap();
+            assert_proofs_valid(&tree);
+        }
+
+        assert_eq!(tree.root(), [0u8; 32]);
+        assert_eq!(tree.remove(0), Err(MerkleError::EmptyTree));
+    }
+
+    #[test]
+    fn test_compact_remove_keeps_error_policy_non_empty() {
+        let options = TreeOptions {
+            empty_root: EmptyRoot::Error,
+            ..Default::default()
+        };
+        let leaves = make_leaves(1);
+        let mut tree =
+            MerkleTree::new_with_options(leaves.clone(), Keccak256Hasher, options).unwrap();
+
+        assert_eq!(tree.remove(0), Err(MerkleError::EmptyTree));
+        assert_eq!(tree.leaves(), &leaves[..]);
+    }
+
+    #[test]
+    fn test_tombstone_remove_keeps_indices() {
+        let tombstone = [0xffu8; 32];
+        let mut leaves = make_leaves(11);
+        let mut tree = tree_with_mode(leaves.clone(), RemoveMode::Tombstone(tombstone));
+
+        for index in [3, 10, 0] {
+            let root = tree.remove(index).unwrap();
+            leaves[index] = tombstone;
+
+            assert_eq!(root, merkle_root(&leaves, &Keccak256Hasher).unwrap());
+            assert_eq!(tree.leaves(), &leaves[..]);
+            assert_proofs_valid(&tree);
+        }
+
+        assert_eq!(tree.leaf_indices(tombstone), vec![0, 3, 10]);
+    }
+
+    #[test]
+    fn test_remove_out_of_range() {
+        let mut tree = tree_with_mode(make_leaves(3), RemoveMode::Compact);
+
+        assert_eq!(
+            tree.remove(3),
+            Err(MerkleError::IndexOutOfRange { index: 3, len: 3 })
+        );
+    }
+}
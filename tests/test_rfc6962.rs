@@ -0,0 +1,214 @@
+//! Test vectors published with RFC 6962, as used by Certificate Transparency
+//! implementations (e.g. `merkle_test.go` in certificate-transparency-go)
+
+#![cfg(feature = "sha2")]
+
+use merkletreers::rfc6962::{
+    hasher, inclusion_proof, leaf_hash, options, root_from_inclusion_proof, tree_from_data,
+    tree_hash, verify_inclusion,
+};
+use merkletreers::tree::MerkleTree;
+use merkletreers::{Hash, MerkleError, OddNodeStrategy, TreeOptions};
+
+const LEAVES: [&str; 8] = [
+    "",
+    "00",
+    "10",
+    "2021",
+    "3031",
+    "40414243",
+    "5051525354555657",
+    "606162636465666768696a6b6c6d6e6f",
+];
+
+const ROOTS: [&str; 8] = [
+    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+    "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+    "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+    "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+    "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+    "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+];
+
+fn data() -> Vec<Vec<u8>> {
+    LEAVES
+        .iter()
+        .map(|leaf| hex::decode(leaf).unwrap())
+        .collect()
+}
+
+fn hash(value: &str) -> Hash {
+    hex::decode(value).unwrap().try_into().unwrap()
+}
+
+/// (leaf index, tree size, audit path)
+fn inclusion_vectors() -> Vec<(usize, usize, Vec<Hash>)> {
+    vec![
+        (0, 1, vec![]),
+        (
+            0,
+            8,
+            vec![
+                hash("96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7"),
+                hash("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                hash("6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"),
+            ],
+        ),
+        (
+            5,
+            8,
+            vec![
+                hash("bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"),
+                hash("ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0"),
+                hash("d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7"),
+            ],
+        ),
+        (
+            2,
+            3,
+            vec![hash(
+                "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+            )],
+        ),
+        (
+            1,
+            5,
+            vec![
+                hash("6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"),
+                hash("5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e"),
+                hash("bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b"),
+            ],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_tree_hash() {
+        assert_eq!(
+            tree_hash::<&[u8]>(&[]),
+            hash("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+    }
+
+    #[test]
+    fn test_tree_hash_vectors() {
+        let data = data();
+
+        for (size, root) in ROOTS.iter().enumerate() {
+            assert_eq!(tree_hash(&data[..=size]), hash(root));
+        }
+    }
+
+    #[test]
+    fn test_tree_hash_after_push() {
+        let data = data();
+        let mut tree = tree_from_data::<&[u8]>(&[]);
+
+        for (item, root) in data.iter().zip(ROOTS) {
+            assert_eq!(tree.push(leaf_hash(item)), hash(root));
+        }
+    }
+
+    #[test]
+    fn test_inclusion_proof_vectors() {
+        let data = data();
+
+        for (index, size, path) in inclusion_vectors() {
+            let tree = tree_from_data(&data[..size]);
+            let leaf = leaf_hash(&data[index]);
+
+            assert_eq!(inclusion_proof(&tree, index, size).unwrap(), path);
+            assert_eq!(
+                verify_inclusion(&path, leaf, index, size, tree.root(), &hasher()),
+                Ok(())
+            );
+        }
+    }
+
+    #[test]
+    fn test_inclusion_proof_vectors_from_the_full_tree() {
+        let data = data();
+        let tree = tree_from_data(&data);
+
+        for (index, size, path) in inclusion_vectors() {
+            assert_eq!(inclusion_proof(&tree, index, size).unwrap(), path);
+        }
+        assert_eq!(
+            inclusion_proof(&tree, 0, data.len() + 1),
+            Err(MerkleError::IndexOutOfRange {
+                index: data.len() + 1,
+                len: data.len()
+            })
+        );
+    }
+
+    #[test]
+    fn test_inclusion_proof_needs_promoted_odd_nodes() {
+        let leaves: Vec<Hash> = data().iter().map(|item| leaf_hash(item)).collect();
+        let options = TreeOptions {
+            odd_node: OddNodeStrategy::Duplicate,
+            ..options()
+        };
+        let tree = MerkleTree::new_with_options(leaves, hasher(), options).unwrap();
+
+        assert_eq!(
+            inclusion_proof(&tree, 0, 5),
+            Err(MerkleError::UnsupportedOddNodeStrategy)
+        );
+    }
+
+    #[test]
+    fn test_every_inclusion_proof_verifies() {
+        let data: Vec<Vec<u8>> = (0..40u8).map(|i| vec![i; i as usize]).collect();
+
+        for size in 1..=data.len() {
+            let tree = tree_from_data(&data[..size]);
+
+            for (index, item) in data[..size].iter().enumerate() {
+                let proof = inclusion_proof(&tree, index, size).unwrap();
+                let root =
+                    root_from_inclusion_proof(&proof, leaf_hash(item), index, size, &hasher());
+
+                assert_eq!(root, Ok(tree.root()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_inclusion_proofs_are_rejected() {
+        let data = data();
+        let (index, size, path) = inclusion_vectors().swap_remove(2);
+        let root = hash(ROOTS[size - 1]);
+        let leaf = leaf_hash(&data[index]);
+
+        assert_eq!(
+            verify_inclusion(&path, leaf_hash(&data[0]), index, size, root, &hasher()),
+            Err(MerkleError::RootMismatch)
+        );
+        assert_eq!(
+            verify_inclusion(&path, leaf, index ^ 1, size, root, &hasher()),
+            Err(MerkleError::RootMismatch)
+        );
+        assert_eq!(
+            verify_inclusion(&path[..2], leaf, index, size, root, &hasher()),
+            Err(MerkleError::MalformedProof)
+        );
+        assert_eq!(
+            verify_inclusion(&path, leaf, index, size + 8, root, &hasher()),
+            Err(MerkleError::MalformedProof)
+        );
+        assert_eq!(
+            verify_inclusion(&path, leaf, size, size, root, &hasher()),
+            Err(MerkleError::IndexOutOfRange {
+                index: size,
+                len: size
+            })
+        );
+    }
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::hash_data;
+use merkletreers::hasher::Keccak256Hasher;
+use merkletreers::merkle_root::{merkle_root, merkle_root_with_options};
+use merkletreers::tree::MerkleTree;
+use merkletreers::{Leaf, MerkleError, TreeOptions};
+
+fn sorted_options(dedup_leaves: bool) -> TreeOptions {
+    TreeOptions {
+        sort_leaves: true,
+        dedup_leaves,
+        ..Default::default()
+    }
+}
+
+fn sorted_tree(leaves: Vec<Leaf>, dedup_leaves: bool) -> MerkleTree {
+    MerkleTree::new_with_options(leaves, Keccak256Hasher, sorted_options(dedup_leaves)).unwrap()
+}
+
+fn assert_index_proofs_valid(tree: &MerkleTree) {
+    for (index, leaf) in tree.original_leaves().iter().enumerate() {
+        let proof = tree.make_proof_at(index).unwrap();
+        assert_eq!(tree.check_proof(proof, *leaf).unwrap(), tree.root());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_set_gives_same_root() {
+        let leaves = hash_data(&["d", "a", "e", "c", "b"]);
+        let shuffled = hash_data(&["b", "e", "a", "d", "c"]);
+
+        let tree = sorted_tree(leaves.clone(), false);
+        let other = sorted_tree(shuffled, false);
+
+        let mut expected = leaves.clone();
+        expected.sort();
+
+        assert_eq!(tree.root(), other.root());
+        assert_eq!(tree.leaves(), &expected[..]);
+        assert_eq!(tree.original_leaves(), &leaves[..]);
+        assert_eq!(
+            tree.root(),
+            merkle_root(&expected, &Keccak256Hasher).unwrap()
+        );
+        assert_eq!(
+            merkle_root_with_options(&leaves, &Keccak256Hasher, &sorted_options(false)).unwrap(),
+            tree.root()
+        );
+    }
+
+    #[test]
+    fn test_dedup_leaves() {
+        let leaves = hash_data(&["b", "a", "b", "c", "a"]);
+        let tree = sorted_tree(leaves.clone(), true);
+
+        let mut expected = hash_data(&["a", "b", "c"]);
+        expected.sort();
+
+        assert_eq!(tree.leaves(), &expected[..]);
+        assert_eq!(
+            tree.root(),
+            sorted_tree(hash_data(&["c", "a", "b"]), false).root()
+        );
+        assert_eq!(
+            merkle_root_with_options(&leaves, &Keccak256Hasher, &sorted_options(true)).unwrap(),
+            tree.root()
+        );
+
+        // both copies of "b" map to the same sorted position
+        assert_eq!(tree.leaf_indices(leaves[0]), vec![0, 2]);
+        assert_eq!(tree.tree_index(0), tree.tree_index(2));
+        assert_eq!(tree.make_proof_at(0), tree.make_proof_at(2));
+        assert_index_proofs_valid(&tree);
+    }
+
+    #[test]
+    fn test_index_proofs_use_original_positions() {
+        let leaves = hash_data(&["m", "e", "r", "k", "l", "e", "t", "r", "e", "e", "r", "s"]);
+        let tree = sorted_tree(leaves.clone(), false);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let position = tree.tree_index(index).unwrap();
+            assert_eq!(&tree.leaves()[position], leaf);
+        }
+        assert_index_proofs_valid(&tree);
+
+        assert_eq!(
+            tree.make_proof_at(12),
+            Err(MerkleError::IndexOutOfRange { index: 12, len: 12 })
+        );
+    }
+
+    #[test]
+    fn test_mutations_keep_tree_sorted() {
+        let mut leaves = hash_data(&["d", "a", "e", "c", "b"]);
+        let mut tree = sorted_tree(leaves.clone(), false);
+
+        let extra = hash_data(&["0", "z"]);
+        tree.push(extra[0]);
+        leaves.push(extra[0]);
+        assert_eq!(tree.root(), sorted_tree(leaves.clone(), false).root());
+        assert_index_proofs_valid(&tree);
+
+        tree.set_leaf(1, extra[1]).unwrap();
+        leaves[1] = extra[1];
+        assert_eq!(tree.root(), sorted_tree(leaves.clone(), false).root());
+        assert_index_proofs_valid(&tree);
+
+        tree.remove(0).unwrap();
+        leaves.remove(0);
+        assert_eq!(tree.original_leaves(), &leaves[..]);
+        assert_eq!(tree.root(), sorted_tree(leaves.clone(), false).root());
+        assert_index_proofs_valid(&tree);
+    }
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::hasher::{Hashable, Keccak256Hasher, SortedPairHasher};
+use merkletreers::merkle_proof_check::merkle_proof_check_sorted;
+use merkletreers::node::proof_hashes;
+use merkletreers::tree::MerkleTree;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_pair_hash_is_commutative() {
+        let leaves = make_leaves(2);
+        let hasher = SortedPairHasher(Keccak256Hasher);
+
+        let mut left_first = [0u8; 32];
+        let mut right_first = [0u8; 32];
+        hasher.hash_nodes(&leaves[0], &leaves[1], &mut left_first);
+        hasher.hash_nodes(&leaves[1], &leaves[0], &mut right_first);
+
+        assert_eq!(left_first, right_first);
+
+        let (min, max) = if leaves[0] < leaves[1] {
+            (leaves[0], leaves[1])
+        } else {
+            (leaves[1], leaves[0])
+        };
+        let mut expected = [0u8; 32];
+        Keccak256Hasher.hash_nodes(&min, &max, &mut expected);
+        assert_eq!(left_first, expected);
+    }
+
+    #[test]
+    fn test_sorted_proofs_without_sides() {
+        for size in 1..=17 {
+            let leaves = make_leaves(size);
+            let tree =
+                MerkleTree::new_with_hasher(leaves.clone(), SortedPairHasher(Keccak256Hasher))
+                    .unwrap();
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = proof_hashes(&tree.make_proof_at(index).unwrap());
+
+                assert_eq!(tree.check_sorted_proof(&proof, *leaf).unwrap(), tree.root());
+                // the sorted check does not rely on the hasher to sort
+                assert_eq!(
+                    merkle_proof_check_sorted(&proof, *leaf, &Keccak256Hasher).unwrap(),
+                    tree.root()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_swapping_siblings_keeps_the_root() {
+        let leaves = make_leaves(6);
+        let mut swapped = leaves.clone();
+        swapped.swap(0, 1);
+        swapped.swap(4, 5);
+
+        let tree = MerkleTree::new_with_hasher(leaves, SortedPairHasher(Keccak256Hasher)).unwrap();
+        let swapped_tree =
+            MerkleTree::new_with_hasher(swapped, SortedPairHasher(Keccak256Hasher)).unwrap();
+
+        assert_eq!(tree.root(), swapped_tree.root());
+    }
+}
@@ -0,0 +1,278 @@
+use merkletreers::hasher::{DomainSeparatedHasher, Hashable, Keccak256Hasher};
+use merkletreers::sparse::{
+    default_hashes, verify_compressed_membership, verify_compressed_non_membership,
+    verify_membership, verify_non_membership, CompressedSparseProof, SparseMerkleTree, DEPTH,
+};
+use merkletreers::utils::hash_it;
+use merkletreers::{Hash, MerkleError, TruncatedHasher};
+
+fn make_key(seed: &str) -> Hash {
+    let mut buffer = [0u8; 32];
+    hash_it(seed.as_bytes(), &mut buffer);
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_root_is_the_default_root() {
+        let tree = SparseMerkleTree::new();
+        let defaults = default_hashes(&Keccak256Hasher);
+
+        assert_eq!(defaults.len(), DEPTH + 1);
+        assert_eq!(tree.root(), defaults[DEPTH]);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_two_leaves_by_hand() {
+        // keys 0 and 1 are siblings at the very bottom of the tree
+        let mut low = [0u8; 32];
+        let mut high = [0u8; 32];
+        high[31] = 1;
+
+        let mut tree = SparseMerkleTree::new();
+        tree.insert(low, b"a");
+        let root = tree.insert(high, b"b");
+
+        let defaults = default_hashes(&Keccak256Hasher);
+        let (mut a, mut b) = ([0u8; 32], [0u8; 32]);
+        hash_it(b"a", &mut a);
+        hash_it(b"b", &mut b);
+        let mut node = [0u8; 32];
+        Keccak256Hasher.hash_nodes(&a, &b, &mut node);
+        for default in &defaults[1..DEPTH] {
+            let left = node;
+            Keccak256Hasher.hash_nodes(&left, default, &mut node);
+        }
+        assert_eq!(root, node);
+
+        low[0] = 0x80;
+        assert_eq!(tree.get(&low), None);
+    }
+
+    #[test]
+    fn test_get_insert_remove() {
+        let mut tree = SparseMerkleTree::new();
+        let empty = tree.root();
+
+        let first = tree.insert(make_key("a"), b"1");
+        tree.insert(make_key("b"), b"2");
+        assert_eq!(tree.get(&make_key("a")), Some(&b"1"[..]));
+        assert_eq!(tree.len(), 2);
+
+        tree.insert(make_key("b"), b"3");
+        assert_eq!(tree.get(&make_key("b")), Some(&b"3"[..]));
+        assert_eq!(tree.len(), 2);
+
+        assert_eq!(tree.remove(&make_key("b")), first);
+        assert_eq!(tree.remove(&make_key("b")), first);
+        assert_eq!(tree.remove(&make_key("a")), empty);
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_root_does_not_depend_on_insertion_order() {
+        let keys: Vec<Hash> = (0..20).map(|i| make_key(&i.to_string())).collect();
+
+        let mut forward = SparseMerkleTree::new();
+        for key in &keys {
+            forward.insert(*key, key);
+        }
+        let mut backward = SparseMerkleTree::new();
+        for key in keys.iter().rev() {
+            backward.insert(*key, key);
+        }
+
+        assert_eq!(forward.root(), backward.root());
+    }
+
+    #[test]
+    fn test_membership_and_non_membership_proofs() {
+        let hasher = DomainSeparatedHasher::new(Keccak256Hasher);
+        let mut tree = SparseMerkleTree::new_with_hasher(hasher);
+        for i in 0..10 {
+            tree.insert(make_key(&i.to_string()), i.to_string().as_bytes());
+        }
+        let root = tree.root();
+
+        let present = make_key("3");
+        let proof = tree.make_proof(&present);
+        assert_eq!(tree.check_membership(&proof, &present, b"3"), Ok(()));
+        assert_eq!(
+            verify_membership(&proof, &present, b"4", root, &hasher),
+            Err(MerkleError::RootMismatch)
+        );
+        assert_eq!(
+            verify_non_membership(&proof, &present, root, &hasher),
+            Err(MerkleError::RootMismatch)
+        );
+
+        let absent = make_key("absent");
+        let proof = tree.make_proof(&absent);
+        assert_eq!(tree.check_non_membership(&proof, &absent), Ok(()));
+        assert_eq!(
+            verify_membership(&proof, &absent, b"3", root, &hasher),
+            Err(MerkleError::RootMismatch)
+        );
+
+        // the proof of a present key does not prove its absent neighbour
+        let mut neighbour = present;
+        neighbour[31] ^= 1;
+        assert_eq!(
+            verify_non_membership(&tree.make_proof(&present), &neighbour, root, &hasher),
+            Err(MerkleError::RootMismatch)
+        );
+        assert_eq!(
+            tree.check_non_membership(&tree.make_proof(&neighbour), &neighbour),
+            Ok(())
+        );
+
+        let mut truncated = proof.clone();
+        truncated.siblings.pop();
+        assert_eq!(
+            tree.check_non_membership(&truncated, &absent),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_proof_after_remove() {
+        let mut tree = SparseMerkleTree::new();
+        tree.insert(make_key("a"), b"1");
+        tree.insert(make_key("b"), b"2");
+        tree.remove(&make_key("a"));
+
+        let proof = tree.make_proof(&make_key("a"));
+        assert_eq!(tree.check_non_membership(&proof, &make_key("a")), Ok(()));
+        let proof = tree.make_proof(&make_key("b"));
+        assert_eq!(tree.check_membership(&proof, &make_key("b"), b"2"), Ok(()));
+    }
+
+    #[test]
+    fn test_compressed_proofs_keep_only_non_default_siblings() {
+        let mut tree = SparseMerkleTree::new();
+        for i in 0..8 {
+            tree.insert(make_key(&i.to_string()), b"value");
+        }
+
+        let key = make_key("3");
+        let proof = tree.make_proof(&key);
+        let compressed = tree.make_compressed_proof(&key);
+
+        // with 8 random keys, only the top few siblings are not empty
+        assert!(compressed.siblings.len() <= 8);
+        assert_eq!(
+            compressed.to_bytes().len(),
+            32 * (compressed.siblings.len() + 1)
+        );
+        assert_eq!(compressed.decompress(tree.defaults()).unwrap(), proof);
+        assert_eq!(
+            CompressedSparseProof::from_bytes(&compressed.to_bytes()).unwrap(),
+            compressed
+        );
+    }
+
+    #[test]
+    fn test_verify_compressed_proofs() {
+        let hasher = DomainSeparatedHasher::new(Keccak256Hasher);
+        let mut tree = SparseMerkleTree::new_with_hasher(hasher);
+        for i in 0..10 {
+            tree.insert(make_key(&i.to_string()), i.to_string().as_bytes());
+        }
+        let root = tree.root();
+
+        let present = make_key("7");
+        let proof = tree.make_compressed_proof(&present);
+        assert_eq!(
+            verify_compressed_membership(&proof, &present, b"7", root, &hasher),
+            Ok(())
+        );
+        assert_eq!(
+            verify_compressed_membership(&proof, &present, b"8", root, &hasher),
+            Err(MerkleError::RootMismatch)
+        );
+
+        let absent = make_key("absent");
+        let proof = tree.make_compressed_proof(&absent);
+        assert_eq!(
+            verify_compressed_non_membership(&proof, &absent, root, &hasher),
+            Ok(())
+        );
+        assert_eq!(
+            verify_compressed_non_membership(&proof, &present, root, &hasher),
+            Err(MerkleError::RootMismatch)
+        );
+
+        // an empty tree proves everything absent with a bare bitmap
+        let empty = SparseMerkleTree::new();
+        let proof = empty.make_compressed_proof(&absent);
+        assert_eq!(proof.bitmap, [0xff; 32]);
+        assert_eq!(proof.to_bytes().len(), 32);
+        assert_eq!(
+            verify_compressed_non_membership(&proof, &absent, empty.root(), &Keccak256Hasher),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_malformed_compressed_proofs() {
+        let mut tree = SparseMerkleTree::new();
+        tree.insert(make_key("a"), b"1");
+        tree.insert(make_key("b"), b"2");
+        let proof = tree.make_compressed_proof(&make_key("a"));
+        let bytes = proof.to_bytes();
+
+        for malformed in [
+            &bytes[..31],
+            &bytes[..bytes.len() - 32],
+            &bytes[..bytes.len() - 1],
+        ] {
+            assert_eq!(
+                CompressedSparseProof::<32>::from_bytes(malformed),
+                Err(MerkleError::MalformedProof)
+            );
+        }
+
+        let mut extra = proof.clone();
+        extra.siblings.push([1u8; 32]);
+        assert_eq!(
+            extra.root(&make_key("a"), Some(b"1"), &Keccak256Hasher),
+            Err(MerkleError::MalformedProof)
+        );
+        assert_eq!(
+            extra.decompress(tree.defaults()),
+            Err(MerkleError::MalformedProof)
+        );
+    }
+
+    #[test]
+    fn test_20_byte_nodes() {
+        let hasher = TruncatedHasher::<Keccak256Hasher>::default();
+        let mut tree: SparseMerkleTree<_, 20> = SparseMerkleTree::new_with_hasher(hasher);
+        for i in 0..10 {
+            tree.insert(make_key(&i.to_string()), i.to_string().as_bytes());
+        }
+        let root = tree.root();
+
+        let present = make_key("3");
+        let proof = tree.make_proof(&present);
+        assert_eq!(
+            verify_membership(&proof, &present, b"3", root, &hasher),
+            Ok(())
+        );
+
+        // the bitmap still has a bit per level, the siblings are 20 bytes
+        let absent = make_key("absent");
+        let proof = tree.make_compressed_proof(&absent);
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), 32 + 20 * proof.siblings.len());
+        assert_eq!(CompressedSparseProof::from_bytes(&bytes), Ok(proof.clone()));
+        assert_eq!(
+            verify_compressed_non_membership(&proof, &absent, root, &hasher),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,226 @@
+#![cfg(feature = "tlog")]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
+use merkletreers::merkle_consistency_proof::merkle_consistency_proof;
+use merkletreers::rfc6962::{hasher, inclusion_proof, leaf_hash, tree_from_data};
+use merkletreers::tlog::{
+    Checkpoint, DirectoryFetcher, NoteSigner, Tile, TileFetcher, TileReader, TileWriter, TlogError,
+};
+use merkletreers::{Leaf, MerkleError};
+
+fn make_entries(count: usize) -> Vec<Vec<u8>> {
+    (0..count).map(|i| i.to_string().into_bytes()).collect()
+}
+
+fn write_tiles(dir: &Path, entries: &[Vec<u8>]) -> TileWriter {
+    let mut writer = TileWriter::create(dir).unwrap();
+    for entry in entries {
+        writer.append(leaf_hash(entry)).unwrap();
+    }
+    writer.flush().unwrap();
+    writer
+}
+
+/// A remote log serving the tiles of a directory, counting the requests
+struct MockServer {
+    tiles: HashMap<String, Vec<u8>>,
+    requests: RefCell<Vec<Tile>>,
+}
+
+impl MockServer {
+    fn new(dir: &Path) -> Self {
+        let mut tiles = HashMap::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            for entry in std::fs::read_dir(path).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    let key = path
+                        .strip_prefix(dir)
+                        .unwrap()
+                        .to_string_lossy()
+                        .to_string();
+                    tiles.insert(key, std::fs::read(path).unwrap());
+                }
+            }
+        }
+
+        MockServer {
+            tiles,
+            requests: RefCell::new(vec![]),
+        }
+    }
+}
+
+impl TileFetcher for MockServer {
+    fn fetch_tile(&self, tile: &Tile) -> Result<Vec<u8>, TlogError> {
+        self.requests.borrow_mut().push(*tile);
+
+        self.tiles
+            .get(&tile.path())
+            .cloned()
+            .ok_or(TlogError::MalformedTile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_paths() {
+        let tile = |level, index, width| Tile {
+            level,
+            index,
+            width,
+        };
+
+        assert_eq!(tile(0, 0, 256).path(), "tile/0/000");
+        assert_eq!(tile(0, 1234067, 256).path(), "tile/0/x001/x234/067");
+        assert_eq!(tile(1, 5, 3).path(), "tile/1/005.p/3");
+        assert_eq!(tile(2, 1000, 255).path(), "tile/2/x001/000.p/255");
+    }
+
+    #[test]
+    fn test_writer_root_matches_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = make_entries(600);
+        let mut writer = TileWriter::create(dir.path()).unwrap();
+        let mut tree = tree_from_data::<&[u8]>(&[]);
+
+        assert_eq!(writer.root(), tree.root());
+        for entry in &entries {
+            writer.append(leaf_hash(entry)).unwrap();
+            tree.push(leaf_hash(entry));
+
+            assert_eq!(writer.root(), tree.root());
+        }
+    }
+
+    #[test]
+    fn test_proofs_match_tree() {
+        for size in [1, 2, 3, 255, 256, 257, 513, 600] {
+            let dir = tempfile::tempdir().unwrap();
+            let entries = make_entries(size);
+            write_tiles(dir.path(), &entries);
+
+            let tree = tree_from_data(&entries);
+            let leaves: Vec<Leaf> = tree.leaves().to_vec();
+            let reader = TileReader::new(DirectoryFetcher::new(dir.path()), size as u64);
+            assert_eq!(reader.root().unwrap(), tree.root());
+
+            for index in (0..size).step_by(7).chain([size - 1]) {
+                assert_eq!(
+                    reader.inclusion_proof(index as u64, size as u64).unwrap(),
+                    inclusion_proof(&tree, index, size).unwrap()
+                );
+            }
+            for old_size in (0..=size).step_by(11).chain([size]) {
+                assert_eq!(
+                    reader
+                        .consistency_proof(old_size as u64, size as u64)
+                        .unwrap(),
+                    merkle_consistency_proof(&leaves, old_size, &hasher()).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_proofs_of_older_trees_from_newer_tiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = make_entries(700);
+        write_tiles(dir.path(), &entries);
+        let reader = TileReader::new(DirectoryFetcher::new(dir.path()), 700);
+
+        for old_size in [1, 100, 256, 300, 512] {
+            let tree = tree_from_data(&entries[..old_size]);
+
+            assert_eq!(
+                reader.inclusion_proof(0, old_size as u64).unwrap(),
+                inclusion_proof(&tree, 0, old_size).unwrap()
+            );
+            assert_eq!(
+                reader
+                    .consistency_proof(old_size as u64 / 2, old_size as u64)
+                    .unwrap(),
+                tree.make_consistency_proof(old_size / 2).unwrap()
+            );
+        }
+
+        assert!(matches!(
+            reader.inclusion_proof(0, 701),
+            Err(TlogError::Merkle(MerkleError::IndexOutOfRange {
+                index: 701,
+                len: 700
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_large_tree_reads_few_tiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let size = 70_000;
+        let entries = make_entries(size);
+        let writer = write_tiles(dir.path(), &entries);
+        let signer = NoteSigner::new("example.com/log", [7u8; 32]).unwrap();
+        let note = writer.checkpoint("example.com/log", &signer).unwrap();
+        let checkpoint = Checkpoint::open(&note, &signer.verifier()).unwrap();
+
+        // files are renamed into place, no temporary file is left behind
+        let server = MockServer::new(dir.path());
+        assert_eq!(server.tiles["checkpoint"], note.as_bytes());
+        assert!(!server.tiles.keys().any(|path| path.ends_with(".tmp")));
+        let reader = TileReader::new(server, checkpoint.size);
+
+        let proof = reader.inclusion_proof(12_345, checkpoint.size).unwrap();
+        assert!(checkpoint
+            .verify_inclusion(&entries[12_345], 12_345, &proof)
+            .is_ok());
+        // one tile per level on the leaf side, plus the right edge
+        assert!(reader.fetcher().requests.borrow().len() <= 6);
+
+        reader.fetcher().requests.borrow_mut().clear();
+        let old = Checkpoint {
+            size: 40_000,
+            root: tree_from_data(&entries[..40_000]).root(),
+            ..checkpoint.clone()
+        };
+        let proof = reader.consistency_proof(old.size, checkpoint.size).unwrap();
+        assert!(old.verify_consistency(&checkpoint, &proof).is_ok());
+        assert!(reader.fetcher().requests.borrow().len() <= 8);
+    }
+
+    #[test]
+    fn test_writer_reopens_and_drops_partial_tiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = make_entries(300);
+        write_tiles(dir.path(), &entries[..200]);
+        assert!(dir.path().join("tile/0/000.p/200").exists());
+
+        let mut writer = TileWriter::open(dir.path(), 200).unwrap();
+        for entry in &entries[200..] {
+            writer.append(leaf_hash(entry)).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert_eq!(writer.size(), 300);
+        assert_eq!(writer.root(), tree_from_data(&entries).root());
+        assert!(dir.path().join("tile/0/000").exists());
+        assert!(!dir.path().join("tile/0/000.p").exists());
+        assert!(dir.path().join("tile/0/001.p/44").exists());
+        assert!(dir.path().join("tile/1/000.p/1").exists());
+
+        // an older size is still served from the full tile
+        let reader = TileReader::new(DirectoryFetcher::new(dir.path()), 200);
+        assert_eq!(
+            reader.root().unwrap(),
+            tree_from_data(&entries[..200]).root()
+        );
+    }
+}
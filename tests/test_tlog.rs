@@ -0,0 +1,184 @@
+#![cfg(feature = "tlog")]
+
+use merkletreers::tlog::{Checkpoint, Log, Note, NoteSigner, NoteVerifier, TlogError};
+use merkletreers::MerkleError;
+
+const SKEY: &str = "PRIVATE+KEY+PeterNeumann+c74f20a3+AYEKFALVFGyNhPJEMzD1QIDr+Y7hfZx09iUvxdXHKDFz";
+const VKEY: &str = "PeterNeumann+c74f20a3+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW";
+const TEXT: &str = "If you think cryptography is the answer to your problem,\n\
+                    then you don't know what your problem is.\n";
+const SIGNED: &str = "If you think cryptography is the answer to your problem,\n\
+                      then you don't know what your problem is.\n\
+                      \n\
+                      \u{2014} PeterNeumann x08go/ZJkuBS9UG/SffcvIAQxVBtiFupLLr8pAcElZInNIuGUgYN1FFYC2pZSNXgKvqfqdngotpRZb6KE6RyyBwJnAM=\n";
+
+fn signer() -> NoteSigner {
+    NoteSigner::new("example.com/log", [7u8; 32]).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Example key and note of Go's `golang.org/x/mod/sumdb/note`
+    #[test]
+    fn test_signed_note_vector() {
+        let signer = NoteSigner::from_private_key(SKEY).unwrap();
+        assert_eq!(signer.private_key(), SKEY);
+        assert_eq!(signer.verifier().public_key(), VKEY);
+
+        let note = Note::sign(TEXT, &[&signer]).unwrap();
+        assert_eq!(note.to_string(), SIGNED);
+
+        let verifier = NoteVerifier::from_public_key(VKEY).unwrap();
+        let parsed = Note::parse(SIGNED).unwrap();
+        assert_eq!(parsed, note);
+        assert!(parsed.verify(&verifier).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_notes_are_rejected() {
+        let verifier = NoteVerifier::from_public_key(VKEY).unwrap();
+
+        let tampered = SIGNED.replace("cryptography", "cryptographY");
+        assert!(matches!(
+            Note::parse(&tampered).unwrap().verify(&verifier),
+            Err(TlogError::InvalidSignature)
+        ));
+        assert!(matches!(
+            Note::parse(SIGNED).unwrap().verify(&signer().verifier()),
+            Err(TlogError::InvalidSignature)
+        ));
+        assert!(matches!(Note::parse(TEXT), Err(TlogError::MalformedNote)));
+        assert!(matches!(
+            NoteVerifier::from_public_key(&VKEY.replace("c74f20a3", "c74f20a4")),
+            Err(TlogError::MalformedKey)
+        ));
+    }
+
+    #[test]
+    fn test_checkpoint_body() {
+        let checkpoint = Checkpoint {
+            origin: "example.com/log".to_string(),
+            size: 3,
+            root: [1u8; 32],
+            extensions: vec!["extra".to_string()],
+        };
+        let body = "example.com/log\n3\nAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=\nextra\n";
+
+        assert_eq!(checkpoint.body(), body);
+        assert_eq!(Checkpoint::parse(body).unwrap(), checkpoint);
+
+        for malformed in [
+            "example.com/log\n3\n",
+            "example.com/log\n03\nAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=\n",
+            "example.com/log\n3\nAQEB\n",
+            "\n3\nAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=\n",
+        ] {
+            assert!(matches!(
+                Checkpoint::parse(malformed),
+                Err(TlogError::MalformedCheckpoint)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_log_proofs_against_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let verifier = signer().verifier();
+        let mut log = Log::create(dir.path(), "example.com/log", signer()).unwrap();
+
+        let mut checkpoints = vec![];
+        for i in 0..20u8 {
+            assert_eq!(log.append(&[i; 3]).unwrap(), i as u64);
+            if i % 7 == 3 {
+                checkpoints.push(Checkpoint::open(&log.checkpoint().unwrap(), &verifier).unwrap());
+            }
+        }
+        checkpoints.push(Checkpoint::open(&log.checkpoint().unwrap(), &verifier).unwrap());
+
+        for checkpoint in &checkpoints {
+            assert_eq!(checkpoint.origin, "example.com/log");
+
+            for index in 0..checkpoint.size {
+                let proof = log.inclusion_proof(index, checkpoint.size).unwrap();
+                let entry = log.entry(index).unwrap();
+                assert!(checkpoint.verify_inclusion(entry, index, &proof).is_ok());
+            }
+        }
+
+        for old in &checkpoints {
+            for new in checkpoints.iter().filter(|new| new.size >= old.size) {
+                let proof = log.consistency_proof(old.size, new.size).unwrap();
+                assert!(old.verify_consistency(new, &proof).is_ok());
+            }
+        }
+
+        let proof = log.inclusion_proof(1, checkpoints[0].size).unwrap();
+        assert!(matches!(
+            checkpoints[0].verify_inclusion(b"forged", 1, &proof),
+            Err(TlogError::Merkle(MerkleError::RootMismatch))
+        ));
+        assert!(matches!(
+            log.inclusion_proof(0, 21),
+            Err(TlogError::Merkle(MerkleError::IndexOutOfRange {
+                index: 21,
+                len: 20
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_log_reopens_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = Log::create(dir.path(), "example.com/log", signer()).unwrap();
+        for i in 0..5u8 {
+            log.append(&[i]).unwrap();
+        }
+        log.checkpoint().unwrap();
+        // not covered by a checkpoint yet, still kept
+        log.append(b"pending").unwrap();
+        let root = log.root();
+        drop(log);
+
+        let mut log = Log::open(dir.path(), signer()).unwrap();
+        assert_eq!(log.origin(), "example.com/log");
+        assert_eq!(log.size(), 6);
+        assert_eq!(log.root(), root);
+        assert_eq!(log.entry(5), Some(&b"pending"[..]));
+
+        log.append(b"more").unwrap();
+        assert_eq!(log.size(), 7);
+
+        assert!(matches!(
+            Log::create(dir.path(), "example.com/log", signer()),
+            Err(TlogError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_rewritten_history_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = Log::create(dir.path(), "example.com/log", signer()).unwrap();
+        for i in 0..5u8 {
+            log.append(&[i]).unwrap();
+        }
+        log.checkpoint().unwrap();
+        drop(log);
+
+        let entries = dir.path().join("entries");
+        let content = std::fs::read_to_string(&entries).unwrap();
+        std::fs::write(&entries, content.replacen("AA==", "AQ==", 1)).unwrap();
+
+        assert!(matches!(
+            Log::open(dir.path(), signer()),
+            Err(TlogError::CheckpointMismatch)
+        ));
+
+        let other = NoteSigner::new("example.com/log", [8u8; 32]).unwrap();
+        assert!(matches!(
+            Log::open(dir.path(), other),
+            Err(TlogError::InvalidSignature)
+        ));
+    }
+}
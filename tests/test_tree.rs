@@ -0,0 +1,162 @@
+mod common;
+
+use common::make_leaves;
+use merkletreers::hasher::Keccak256Hasher;
+use merkletreers::merkle_proof_mixed::merkle_proof_mixed_tree;
+use merkletreers::merkle_root::merkle_root;
+use merkletreers::tree::MerkleTree;
+use merkletreers::MerkleError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_layers_match_merkle_root() {
+        for size in 1..=33 {
+            let leaves = make_leaves(size);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+            assert_eq!(tree.root(), merkle_root(&leaves, &Keccak256Hasher).unwrap());
+            assert_eq!(tree.layers()[0], leaves);
+            assert_eq!(tree.layers().last().unwrap(), &vec![tree.root()]);
+        }
+    }
+
+    #[test]
+    fn test_cached_proofs_match_mixed_tree_proofs() {
+        for size in 1..=33 {
+            let leaves = make_leaves(size);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+            for leaf in &leaves {
+                let proof = tree.make_proof(*leaf).unwrap();
+                assert_eq!(
+                    proof,
+                    merkle_proof_mixed_tree(&leaves, *leaf, &Keccak256Hasher).unwrap()
+                );
+                assert_eq!(tree.check_proof(proof, *leaf).unwrap(), tree.root());
+            }
+        }
+    }
+
+    #[test]
+    fn test_push_matches_tree_built_from_scratch() {
+        let leaves = make_leaves(70);
+        let mut tree = MerkleTree::new(vec![]).unwrap();
+
+        for (size, leaf) in leaves.iter().enumerate() {
+            let root = tree.push(*leaf);
+            let expected = MerkleTree::new(leaves[..=size].to_vec()).unwrap();
+
+            assert_eq!(
+                root,
+                merkle_root(&leaves[..=size], &Keccak256Hasher).unwrap()
+            );
+            assert_eq!(tree.layers(), expected.layers());
+        }
+    }
+
+    #[test]
+    fn test_extend_matches_tree_built_from_scratch() {
+        let leaves = make_leaves(45);
+
+        for split in 0..=leaves.len() {
+            let mut tree = MerkleTree::new(leaves[..split].to_vec()).unwrap();
+            let root = tree.extend(leaves[split..].iter().copied());
+
+            assert_eq!(root, merkle_root(&leaves, &Keccak256Hasher).unwrap());
+            assert_eq!(
+                tree.layers(),
+                MerkleTree::new(leaves.clone()).unwrap().layers()
+            );
+        }
+    }
+
+    #[test]
+    fn test_proofs_after_push() {
+        let leaves = make_leaves(13);
+        let mut tree = MerkleTree::new(leaves[..5].to_vec()).unwrap();
+        for leaf in &leaves[5..] {
+            tree.push(*leaf);
+        }
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.make_proof_at(index).unwrap();
+            assert_eq!(tree.check_proof(proof, *leaf).unwrap(), tree.root());
+        }
+    }
+
+    #[test]
+    fn test_from_iterator_and_extend_trait() {
+        let leaves = make_leaves(9);
+
+        let tree: MerkleTree = leaves[..4].iter().copied().collect();
+        assert_eq!(tree.leaves(), &leaves[..4]);
+
+        let mut tree = tree;
+        Extend::extend(&mut tree, leaves[4..].iter().copied());
+        assert_eq!(tree.root(), merkle_root(&leaves, &Keccak256Hasher).unwrap());
+    }
+
+    #[test]
+    fn test_set_leaf_matches_tree_built_from_scratch() {
+        for size in 1..=20 {
+            let mut leaves = make_leaves(size);
+            let mut tree = MerkleTree::new(leaves.clone()).unwrap();
+
+            for index in 0..size {
+                let new_leaf = make_leaves(size + index + 1)[size + index];
+                leaves[index] = new_leaf;
+
+                let root = tree.set_leaf(index, new_leaf).unwrap();
+                assert_eq!(root, merkle_root(&leaves, &Keccak256Hasher).unwrap());
+                assert_eq!(
+                    tree.layers(),
+                    MerkleTree::new(leaves.clone()).unwrap().layers()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_leaves_batch() {
+        let mut leaves = make_leaves(23);
+        let replacements = make_leaves(30);
+        let mut tree = MerkleTree::new(leaves.clone()).unwrap();
+
+        let updates = vec![
+            (22, replacements[22]),
+            (0, replacements[0]),
+            (7, replacements[7]),
+            (6, replacements[6]),
+            (7, replacements[29]),
+        ];
+        for (index, leaf) in &updates {
+            leaves[*index] = *leaf;
+        }
+
+        let root = tree.set_leaves(updates).unwrap();
+        assert_eq!(root, merkle_root(&leaves, &Keccak256Hasher).unwrap());
+        assert_eq!(tree.leaves()[7], replacements[29]);
+
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.make_proof_at(index).unwrap();
+            assert_eq!(tree.check_proof(proof, *leaf).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn test_set_leaves_out_of_range_changes_nothing() {
+        let leaves = make_leaves(5);
+        let mut tree = MerkleTree::new(leaves.clone()).unwrap();
+        let root = tree.root();
+
+        assert_eq!(
+            tree.set_leaves([(0, [0u8; 32]), (5, [0u8; 32])]),
+            Err(MerkleError::IndexOutOfRange { index: 5, len: 5 })
+        );
+        assert_eq!(tree.leaves(), &leaves[..]);
+        assert_eq!(tree.root(), root);
+    }
+}